@@ -0,0 +1,50 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::variables::VariableTable;
+
+/// Source of truth values for the variables of a formula.
+pub trait Assignment {
+    /// Value given to `variable`, or `None` when the assignment does not cover it.
+    fn value(&self, variable: &str) -> Option<bool>;
+}
+
+impl Assignment for HashMap<String, bool> {
+    fn value(&self, variable: &str) -> Option<bool> {
+        self.get(variable).copied()
+    }
+}
+
+impl Assignment for HashMap<&str, bool> {
+    fn value(&self, variable: &str) -> Option<bool> {
+        self.get(variable).copied()
+    }
+}
+
+impl Assignment for BTreeMap<String, bool> {
+    fn value(&self, variable: &str) -> Option<bool> {
+        self.get(variable).copied()
+    }
+}
+
+/// Packs the values of up to 64 interned variables in a word: bit `i` holds the value of the
+/// variable with index `i` in the table.
+#[derive(Debug, Clone, Copy)]
+pub struct Bitmask<'a> {
+    variables: &'a VariableTable,
+    bits: u64,
+}
+
+impl<'a> Bitmask<'a> {
+    pub fn new(variables: &'a VariableTable, bits: u64) -> Self {
+        Bitmask { variables, bits }
+    }
+}
+
+impl Assignment for Bitmask<'_> {
+    fn value(&self, variable: &str) -> Option<bool> {
+        self.variables
+            .index(variable)
+            .filter(|i| *i < 64)
+            .map(|i| self.bits & (1 << i) != 0)
+    }
+}
@@ -1,84 +1,152 @@
-use core::panic;
+use std::collections::BTreeSet;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+use crate::error::FormulaError;
+use crate::operation::operator::Operator;
+use crate::operation::rpn::{join_rpn, tokenize_rpn, RpnToken};
+use crate::operation::truth_table::TruthTable;
+
+use super::assignment::Assignment;
+use super::normal_form::{Clause, Dnf, Literal};
+use super::variables::VariableTable;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Evaluator {
-    Variable(char),
+    True,
+    False,
+    Variable(String),
     And(Box<Evaluator>, Box<Evaluator>),
     Or(Box<Evaluator>, Box<Evaluator>),
     Not(Box<Evaluator>),
     Xor(Box<Evaluator>, Box<Evaluator>),
     Equivalence(Box<Evaluator>, Box<Evaluator>),
     Conditional(Box<Evaluator>, Box<Evaluator>),
+    Nand(Box<Evaluator>, Box<Evaluator>),
+    Nor(Box<Evaluator>, Box<Evaluator>),
+    Xnor(Box<Evaluator>, Box<Evaluator>),
+    /// `Converse(A, B)` is the reverse implication `A <- B`.
+    Converse(Box<Evaluator>, Box<Evaluator>),
+    /// `IfThenElse(C, T, E)` selects `T` when `C` holds and `E` otherwise, like a multiplexer.
+    IfThenElse(Box<Evaluator>, Box<Evaluator>, Box<Evaluator>),
 }
 
 impl Evaluator {
     pub fn new(formula: &str) -> Self {
+        Self::try_new(formula).unwrap_or_else(|e| panic!("Invalid formula: {formula}, {e}"))
+    }
+
+    /// Builds the tree of an RPN formula, reporting malformed input instead of panicking.
+    pub fn try_new(formula: &str) -> Result<Self, FormulaError> {
         let mut stack: Vec<Evaluator> = vec![];
 
-        for c in formula.chars() {
-            match c {
-                'A'..='Z' => stack.push(Evaluator::Variable(c)),
-                '&' => {
-                    let right = stack.pop().unwrap();
-                    let left = stack.pop().unwrap();
-                    stack.push(Evaluator::And(Box::new(left), Box::new(right)));
-                }
-                '|' => {
-                    let right = stack.pop().unwrap();
-                    let left = stack.pop().unwrap();
-                    stack.push(Evaluator::Or(Box::new(left), Box::new(right)));
-                }
-                '^' => {
-                    let right = stack.pop().unwrap();
-                    let left = stack.pop().unwrap();
-                    stack.push(Evaluator::Xor(Box::new(left), Box::new(right)));
-                }
-                '=' => {
-                    let right = stack.pop().unwrap();
-                    let left = stack.pop().unwrap();
-                    stack.push(Evaluator::Equivalence(Box::new(left), Box::new(right)));
-                }
-                '>' => {
-                    let right = stack.pop().unwrap();
-                    let left = stack.pop().unwrap();
-                    stack.push(Evaluator::Conditional(Box::new(left), Box::new(right)));
-                }
-                '!' => {
-                    let operand = stack.pop().unwrap();
-                    stack.push(Evaluator::Not(Box::new(operand)));
+        for (position, token) in tokenize_rpn(formula)? {
+            match token {
+                RpnToken::Constant(value) => stack.push(if value { Evaluator::True } else { Evaluator::False }),
+                RpnToken::Variable(name) => stack.push(Evaluator::Variable(name)),
+                RpnToken::Operator(operator) => {
+                    let arity = operator.arity();
+                    if stack.len() < arity {
+                        let token = operator.info().symbol.to_string();
+                        return Err(FormulaError::MissingOperand { position, token });
+                    }
+                    let operands = stack.split_off(stack.len() - arity);
+                    stack.push(Evaluator::from_operator(operator, operands));
                 }
-                _ => panic!("Invalid character in formula: {}", c),
             }
         }
-        if stack.len() != 1 {
-            panic!("Invalid formula: {}, operations left: {:?}", formula, stack);
+
+        match stack.len() {
+            0 => Err(FormulaError::EmptyFormula),
+            1 => Ok(stack.pop().unwrap()),
+            count => Err(FormulaError::DanglingOperands { position: formula.chars().count(), count }),
+        }
+    }
+
+    /// Builds an `operator` node. `operands` must hold exactly `operator.arity()` trees.
+    pub fn from_operator(operator: Operator, operands: Vec<Evaluator>) -> Self {
+        assert_eq!(operands.len(), operator.arity(), "wrong operand count for {}", operator.info().name);
+        let mut operands = operands.into_iter().map(Box::new);
+        let mut next = || operands.next().unwrap();
+        match operator {
+            Operator::Not => Evaluator::Not(next()),
+            Operator::And => Evaluator::And(next(), next()),
+            Operator::Or => Evaluator::Or(next(), next()),
+            Operator::Xor => Evaluator::Xor(next(), next()),
+            Operator::Equivalence => Evaluator::Equivalence(next(), next()),
+            Operator::Conditional => Evaluator::Conditional(next(), next()),
+            Operator::Nand => Evaluator::Nand(next(), next()),
+            Operator::Nor => Evaluator::Nor(next(), next()),
+            Operator::Xnor => Evaluator::Xnor(next(), next()),
+            Operator::Converse => Evaluator::Converse(next(), next()),
+            Operator::IfThenElse => Evaluator::IfThenElse(next(), next(), next()),
+        }
+    }
+
+    /// Operator at the root of the tree with its operands in order, `None` for constants and variables.
+    pub fn operator(&self) -> Option<(Operator, Vec<&Evaluator>)> {
+        Some(match self {
+            Evaluator::True | Evaluator::False | Evaluator::Variable(_) => return None,
+            Evaluator::Not(operand) => (Operator::Not, vec![operand]),
+            Evaluator::And(left, right) => (Operator::And, vec![left, right]),
+            Evaluator::Or(left, right) => (Operator::Or, vec![left, right]),
+            Evaluator::Xor(left, right) => (Operator::Xor, vec![left, right]),
+            Evaluator::Equivalence(left, right) => (Operator::Equivalence, vec![left, right]),
+            Evaluator::Conditional(left, right) => (Operator::Conditional, vec![left, right]),
+            Evaluator::Nand(left, right) => (Operator::Nand, vec![left, right]),
+            Evaluator::Nor(left, right) => (Operator::Nor, vec![left, right]),
+            Evaluator::Xnor(left, right) => (Operator::Xnor, vec![left, right]),
+            Evaluator::Converse(left, right) => (Operator::Converse, vec![left, right]),
+            Evaluator::IfThenElse(condition, then, otherwise) => (Operator::IfThenElse, vec![condition, then, otherwise]),
+        })
+    }
+
+    /// Evaluates the formula, reading each variable from `assignment`.
+    pub fn eval<A: Assignment + ?Sized>(&self, assignment: &A) -> Result<bool, FormulaError> {
+        match self {
+            Evaluator::True => Ok(true),
+            Evaluator::False => Ok(false),
+            Evaluator::Variable(name) => assignment
+                .value(name)
+                .ok_or_else(|| FormulaError::UnassignedVariable { variable: name.clone() }),
+            _ => {
+                let (operator, operands) = self.operator().unwrap();
+                let values = operands
+                    .into_iter()
+                    .map(|operand| operand.eval(assignment))
+                    .collect::<Result<Vec<bool>, FormulaError>>()?;
+                Ok(operator.apply(&values))
+            }
         }
-        stack.pop().unwrap()
-    }
-
-    pub fn to_string(&self) -> String {
-        let res = match self {
-            Evaluator::Variable(c) => c.to_string(),
-            Evaluator::And(left, right) => format!("{}{}&", left.to_string(), right.to_string()),
-            Evaluator::Or(left, right) => format!("{}{}|", left.to_string(), right.to_string()),
-            Evaluator::Not(operand) => format!("{}!", operand.to_string()),
-            Evaluator::Xor(left, right) => format!("{}{}^", left.to_string(), right.to_string()),
-            Evaluator::Equivalence(left, right) => format!("{}{}=", left.to_string(), right.to_string()),
-            Evaluator::Conditional(left, right) => format!("{}{}>", left.to_string(), right.to_string()),
-        };
-        if !res.contains(['|', '^', '=', '>']) || !res.contains(['&', '^', '=', '>']) {
-            res.as_str().chars().filter(|c| c != &'&' && c != &'|').collect::<String>() +
-            &res.as_str().chars().filter(|c| c == &'&' || c == &'|').collect::<String>()
-        } else {
-            res
+    }
+
+    /// Sorted, deduplicated variables appearing in the formula.
+    pub fn variables(&self) -> Vec<String> {
+        fn collect<'a>(evaluator: &'a Evaluator, variables: &mut BTreeSet<&'a str>) {
+            if let Evaluator::Variable(name) = evaluator {
+                variables.insert(name);
+            }
+            for operand in evaluator.operator().map(|(_, operands)| operands).unwrap_or_default() {
+                collect(operand, variables);
+            }
         }
+
+        let mut variables = BTreeSet::new();
+        collect(self, &mut variables);
+        variables.into_iter().map(str::to_string).collect()
+    }
+
+    /// Interning table of the formula's variables, indexed in sorted name order.
+    pub fn variable_table(&self) -> VariableTable {
+        self.variables().into_iter().collect()
     }
 
     pub fn to_negation_normal_form(&self) -> Self {
         match self {
-            Evaluator::Variable(_) => self.clone(),
+            Evaluator::True | Evaluator::False | Evaluator::Variable(_) => self.clone(),
             Evaluator::Not(inner) => {
                 match **inner {
+                    Evaluator::True => Evaluator::False,
+                    Evaluator::False => Evaluator::True,
                     Evaluator::Variable(_) => self.clone(),
                     Evaluator::Not(ref inner_inner) => {
                         // Double negation elimination
@@ -103,9 +171,9 @@ impl Evaluator {
                         )
                     },
                     _ => {
-                        // For other operations, process inner first, then negate
+                        // For other operations, rewrite inner into AND/OR first, then push the negation down
                         let inner_nnf = inner.to_negation_normal_form();
-                        Evaluator::Not(Box::new(inner_nnf))
+                        Evaluator::Not(Box::new(inner_nnf)).to_negation_normal_form()
                     }
                 }
             },
@@ -157,13 +225,40 @@ impl Evaluator {
                     Box::new(not_left.to_negation_normal_form()),
                     Box::new(right_nnf)
                 )
+            },
+            Evaluator::Nand(left, right) => {
+                // A NAND B is the same as NOT(A AND B)
+                let and = Evaluator::And(left.clone(), right.clone());
+                Evaluator::Not(Box::new(and)).to_negation_normal_form()
+            },
+            Evaluator::Nor(left, right) => {
+                // A NOR B is the same as NOT(A OR B)
+                let or = Evaluator::Or(left.clone(), right.clone());
+                Evaluator::Not(Box::new(or)).to_negation_normal_form()
+            },
+            Evaluator::Xnor(left, right) => {
+                // A XNOR B is the same as A ≡ B
+                Evaluator::Equivalence(left.clone(), right.clone()).to_negation_normal_form()
+            },
+            Evaluator::Converse(left, right) => {
+                // A ← B is the same as B → A
+                Evaluator::Conditional(right.clone(), left.clone()).to_negation_normal_form()
+            },
+            Evaluator::IfThenElse(condition, then, otherwise) => {
+                // C ? T : E is the same as (C AND T) OR (NOT(C) AND E)
+                let when_true = Evaluator::And(condition.clone(), then.clone());
+                let when_false = Evaluator::And(Box::new(Evaluator::Not(condition.clone())), otherwise.clone());
+                Evaluator::Or(Box::new(when_true), Box::new(when_false)).to_negation_normal_form()
             }
         }
     }
 
     fn to_conjunctive_normal_form_callback(&self) -> Self {
         match self {
-            Evaluator::Variable(_) | Evaluator::Not(box Evaluator::Variable(_)) => self.clone(),
+            Evaluator::True
+            | Evaluator::False
+            | Evaluator::Variable(_)
+            | Evaluator::Not(box Evaluator::Variable(_)) => self.clone(),
         
             Evaluator::And(left, right) => {
                 let left_cnf = left.to_conjunctive_normal_form_callback();
@@ -214,22 +309,177 @@ impl Evaluator {
             },
         
             // These operators should have been eliminated by NNF conversion
-            Evaluator::Xor(_, _) | Evaluator::Equivalence(_, _) | Evaluator::Conditional(_, _) => {
+            Evaluator::Xor(_, _)
+            | Evaluator::Equivalence(_, _)
+            | Evaluator::Conditional(_, _)
+            | Evaluator::Nand(_, _)
+            | Evaluator::Nor(_, _)
+            | Evaluator::Xnor(_, _)
+            | Evaluator::Converse(_, _)
+            | Evaluator::IfThenElse(_, _, _) => {
                 self.to_negation_normal_form().to_conjunctive_normal_form_callback()
             }
         }
     }
 
+    /// Removes every `True` and `False` node, leaving either a constant-free tree or a single constant.
+    pub fn fold_constants(&self) -> Self {
+        use Evaluator::{False, True};
+
+        match self {
+            True | False | Evaluator::Variable(_) => self.clone(),
+            Evaluator::Not(operand) => match operand.fold_constants() {
+                True => False,
+                False => True,
+                operand => Evaluator::Not(Box::new(operand)),
+            },
+            Evaluator::And(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (False, _) | (_, False) => False,
+                (True, other) | (other, True) => other,
+                (left, right) => Evaluator::And(Box::new(left), Box::new(right)),
+            },
+            Evaluator::Or(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (True, _) | (_, True) => True,
+                (False, other) | (other, False) => other,
+                (left, right) => Evaluator::Or(Box::new(left), Box::new(right)),
+            },
+            Evaluator::Xor(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (False, other) | (other, False) => other,
+                (True, other) | (other, True) => Evaluator::Not(Box::new(other)).fold_constants(),
+                (left, right) => Evaluator::Xor(Box::new(left), Box::new(right)),
+            },
+            Evaluator::Equivalence(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (True, other) | (other, True) => other,
+                (False, other) | (other, False) => Evaluator::Not(Box::new(other)).fold_constants(),
+                (left, right) => Evaluator::Equivalence(Box::new(left), Box::new(right)),
+            },
+            Evaluator::Conditional(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (False, _) | (_, True) => True,
+                (True, other) => other,
+                (other, False) => Evaluator::Not(Box::new(other)),
+                (left, right) => Evaluator::Conditional(Box::new(left), Box::new(right)),
+            },
+            Evaluator::Nand(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (left @ (True | False), right) | (left, right @ (True | False)) => {
+                    Evaluator::Not(Box::new(Evaluator::And(Box::new(left), Box::new(right)))).fold_constants()
+                }
+                (left, right) => Evaluator::Nand(Box::new(left), Box::new(right)),
+            },
+            Evaluator::Nor(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (left @ (True | False), right) | (left, right @ (True | False)) => {
+                    Evaluator::Not(Box::new(Evaluator::Or(Box::new(left), Box::new(right)))).fold_constants()
+                }
+                (left, right) => Evaluator::Nor(Box::new(left), Box::new(right)),
+            },
+            Evaluator::Xnor(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (left @ (True | False), right) | (left, right @ (True | False)) => {
+                    Evaluator::Equivalence(Box::new(left), Box::new(right)).fold_constants()
+                }
+                (left, right) => Evaluator::Xnor(Box::new(left), Box::new(right)),
+            },
+            Evaluator::Converse(left, right) => match (left.fold_constants(), right.fold_constants()) {
+                (left @ (True | False), right) | (left, right @ (True | False)) => {
+                    Evaluator::Conditional(Box::new(right), Box::new(left)).fold_constants()
+                }
+                (left, right) => Evaluator::Converse(Box::new(left), Box::new(right)),
+            },
+            Evaluator::IfThenElse(condition, then, otherwise) => {
+                match (condition.fold_constants(), then.fold_constants(), otherwise.fold_constants()) {
+                    (True, then, _) => then,
+                    (False, _, otherwise) => otherwise,
+                    (condition, then @ (True | False), otherwise) | (condition, then, otherwise @ (True | False)) => {
+                        let when_true = Evaluator::And(Box::new(condition.clone()), Box::new(then));
+                        let when_false = Evaluator::And(Box::new(Evaluator::Not(Box::new(condition))), Box::new(otherwise));
+                        Evaluator::Or(Box::new(when_true), Box::new(when_false)).fold_constants()
+                    }
+                    (condition, then, otherwise) => {
+                        Evaluator::IfThenElse(Box::new(condition), Box::new(then), Box::new(otherwise))
+                    }
+                }
+            }
+        }
+    }
+
     pub fn to_conjunctive_normal_form(&self) -> Self {
         self
             .to_negation_normal_form()
             .to_conjunctive_normal_form_callback()
     }
+
+    /// Disjunction of conjunctions of literals equivalent to the formula, without repeated
+    /// literals, contradictory terms, or terms absorbed by a smaller one. `False` when no term
+    /// is left, `True` when a term is empty.
+    pub fn to_disjunctive_normal_form(&self) -> Self {
+        Dnf::from_evaluator(self).to_evaluator()
+    }
+
+    /// Sum of the minterms of the formula: one term per satisfying row of its truth table,
+    /// each assigning every variable, in row order.
+    pub fn to_canonical_disjunctive_normal_form(&self) -> Result<Self, FormulaError> {
+        let table = TruthTable::new(self)?;
+        let terms = table
+            .minterms()
+            .into_iter()
+            .map(|index| {
+                let values = table.row(index).values;
+                let names = table.variables().iter().cloned();
+                Clause::new(names.zip(values).map(|(variable, value)| Literal { variable, negated: !value }))
+            })
+            .collect();
+        Ok(Dnf::new(terms).to_evaluator())
+    }
+}
+
+impl fmt::Display for Evaluator {
+    /// Prints the tree in RPN. Chains of `&` or `|` are flattened so their operators are
+    /// grouped at the end (`ABCD|||`), and tokens are space-separated as soon as one
+    /// identifier would not read back as a single compact token.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn flatten<'a>(evaluator: &'a Evaluator, operator: Operator, out: &mut Vec<&'a Evaluator>) {
+            match evaluator.operator() {
+                Some((inner, operands)) if inner == operator => {
+                    for operand in operands {
+                        flatten(operand, operator, out);
+                    }
+                }
+                _ => out.push(evaluator),
+            }
+        }
+
+        fn tokens(evaluator: &Evaluator, out: &mut Vec<String>) {
+            let (operator, operands) = match evaluator {
+                Evaluator::True => return out.push("1".to_string()),
+                Evaluator::False => return out.push("0".to_string()),
+                Evaluator::Variable(name) => return out.push(name.clone()),
+                _ => evaluator.operator().unwrap(),
+            };
+            let operands = if matches!(operator, Operator::And | Operator::Or) {
+                let mut chain = vec![];
+                flatten(evaluator, operator, &mut chain);
+                chain
+            } else {
+                operands
+            };
+            let symbols = operands.len() + 1 - operator.arity();
+            for operand in operands {
+                tokens(operand, out);
+            }
+            out.extend(std::iter::repeat_n(operator.info().symbol.to_string(), symbols));
+        }
+
+        let mut res = vec![];
+        tokens(self, &mut res);
+        write!(f, "{}", join_rpn(&res))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
+    use crate::boolean_evaluator::assignment::Bitmask;
+    use crate::solver::validity::{equivalent, Verdict};
 
     #[test]
     fn test_evaluator_to_string() {
@@ -237,91 +487,315 @@ mod tests {
         assert_eq!(formula.to_string(), "AB&C|");
     }
 
+    #[test]
+    fn test_evaluator_to_string_flattens_chains() {
+        assert_eq!(Evaluator::new("AB&!C&").to_string(), "AB&!C&");
+        assert_eq!(Evaluator::new("AB|C|D^").to_string(), "ABC||D^");
+        assert_eq!(Evaluator::new("AB&C&!").to_string(), "ABC&&!");
+    }
+
+    #[test]
+    fn test_try_new_invalid_character() {
+        assert_eq!(
+            Evaluator::try_new("AB&c|").unwrap_err(),
+            FormulaError::InvalidCharacter { position: 3, token: "c".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_try_new_missing_operand() {
+        assert_eq!(
+            Evaluator::try_new("A&").unwrap_err(),
+            FormulaError::MissingOperand { position: 1, token: "&".to_string() }
+        );
+        assert_eq!(
+            Evaluator::try_new("!").unwrap_err(),
+            FormulaError::MissingOperand { position: 0, token: "!".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_try_new_dangling_operands() {
+        assert_eq!(
+            Evaluator::try_new("AB&C").unwrap_err(),
+            FormulaError::DanglingOperands { position: 4, count: 2 }
+        );
+        assert_eq!(Evaluator::try_new("").unwrap_err(), FormulaError::EmptyFormula);
+    }
+
+    #[test]
+    fn test_variables() {
+        assert_eq!(Evaluator::new("CA&B|A>").variables(), vec!["A", "B", "C"]);
+    }
+
+    #[test]
+    fn test_eval_with_map() {
+        let formula = Evaluator::new("AB>C^");
+        let assignment = HashMap::from([("A", true), ("B", false), ("C", false)]);
+        assert_eq!(formula.eval(&assignment), Ok(false));
+        let assignment = HashMap::from([("A", true), ("B", true)]);
+        assert_eq!(
+            formula.eval(&assignment),
+            Err(FormulaError::UnassignedVariable { variable: "C".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_eval_with_bitmask() {
+        let formula = Evaluator::new("AB=C!&");
+        let variables = formula.variable_table();
+        let outputs = (0..8)
+            .map(|bits| formula.eval(&Bitmask::new(&variables, bits)).unwrap())
+            .collect::<Vec<bool>>();
+        assert_eq!(outputs, vec![true, false, false, true, false, false, false, false]);
+    }
+
+    #[test]
+    fn test_constants() {
+        let formula = Evaluator::new("A1&0|");
+        assert_eq!(formula.to_string(), "A1&0|");
+        assert_eq!(formula.eval(&HashMap::from([("A", true)])), Ok(true));
+        assert_eq!(formula.variables(), vec!["A"]);
+        assert_eq!(Evaluator::new("1!A|").to_negation_normal_form().to_string(), "0A|");
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        assert_eq!(Evaluator::new("A1&").fold_constants().to_string(), "A");
+        assert_eq!(Evaluator::new("A0|!").fold_constants().to_string(), "A!");
+        assert_eq!(Evaluator::new("A1^B0=&").fold_constants().to_string(), "A!B!&");
+        assert_eq!(Evaluator::new("0A>B|").fold_constants(), Evaluator::True);
+        assert_eq!(Evaluator::new("AB&1!&").fold_constants(), Evaluator::False);
+        assert_eq!(Evaluator::new("AB&C>").fold_constants(), Evaluator::new("AB&C>"));
+    }
+
+    #[test]
+    fn test_identifiers() {
+        let formula = Evaluator::new("door_open x17 & ! alarm >");
+        assert_eq!(formula.variables(), vec!["alarm", "door_open", "x17"]);
+        assert_eq!(formula.to_string(), "door_open x17 & ! alarm >");
+        assert_eq!(formula.to_negation_normal_form().to_string(), "door_open x17 & alarm |");
+        let table = formula.variable_table();
+        assert_eq!(table.index("x17"), Some(2));
+        assert_eq!(formula.eval(&Bitmask::new(&table, 0b110)), Ok(true));
+        assert_eq!(formula.eval(&Bitmask::new(&table, 0b010)), Ok(false));
+        assert_eq!(Evaluator::new("A B C | &"), Evaluator::new("ABC|&"));
+        assert_eq!(
+            Evaluator::try_new("x17 2y &").unwrap_err(),
+            FormulaError::InvalidCharacter { position: 4, token: "2y".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_extended_operators() {
+        let formula = Evaluator::new("AB/CD#%EEF<?");
+        assert_eq!(formula.to_string(), "AB/CD#%EEF<?");
+        let table = formula.variable_table();
+        for bits in 0..64u64 {
+            let value = |i: u64| bits & (1 << i) != 0;
+            let (a, b, c, d, e, f) = (value(0), value(1), value(2), value(3), value(4), value(5));
+            let condition = (a & b) == (c | d);
+            let expected = if condition { e } else { e | !f };
+            let assignment = Bitmask::new(&table, bits);
+            assert_eq!(formula.eval(&assignment), Ok(expected));
+            assert_eq!(formula.to_negation_normal_form().eval(&assignment), Ok(expected));
+            assert_eq!(formula.to_conjunctive_normal_form().eval(&assignment), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_negation_normal_form_extended_operators() {
+        for formula in ["AB/", "AB#", "AB<", "AB/!", "ABC?", "AB^!", "AB%C=!", "ABC?!D<"] {
+            assert_negation_normal_form(formula);
+        }
+    }
+
+    #[test]
+    fn test_fold_constants_extended_operators() {
+        assert_eq!(Evaluator::new("A1/").fold_constants().to_string(), "A!");
+        assert_eq!(Evaluator::new("A0#").fold_constants().to_string(), "A!");
+        assert_eq!(Evaluator::new("A0%").fold_constants().to_string(), "A!");
+        assert_eq!(Evaluator::new("A0<").fold_constants(), Evaluator::True);
+        assert_eq!(Evaluator::new("1AB?").fold_constants().to_string(), "A");
+        assert_eq!(Evaluator::new("A10?").fold_constants().to_string(), "A");
+        assert_eq!(Evaluator::new("ABC?").fold_constants(), Evaluator::new("ABC?"));
+    }
+
+    fn is_literal(evaluator: &Evaluator) -> bool {
+        matches!(
+            evaluator,
+            Evaluator::True | Evaluator::False | Evaluator::Variable(_) | Evaluator::Not(box Evaluator::Variable(_))
+        )
+    }
+
+    fn is_negation_normal_form(evaluator: &Evaluator) -> bool {
+        match evaluator {
+            Evaluator::And(left, right) | Evaluator::Or(left, right) => {
+                is_negation_normal_form(left) && is_negation_normal_form(right)
+            }
+            _ => is_literal(evaluator),
+        }
+    }
+
+    fn is_clause(evaluator: &Evaluator) -> bool {
+        match evaluator {
+            Evaluator::Or(left, right) => is_clause(left) && is_clause(right),
+            _ => is_literal(evaluator),
+        }
+    }
+
+    fn is_conjunctive_normal_form(evaluator: &Evaluator) -> bool {
+        match evaluator {
+            Evaluator::And(left, right) => is_conjunctive_normal_form(left) && is_conjunctive_normal_form(right),
+            _ => is_clause(evaluator),
+        }
+    }
+
+    fn is_term(evaluator: &Evaluator) -> bool {
+        match evaluator {
+            Evaluator::And(left, right) => is_term(left) && is_term(right),
+            _ => is_literal(evaluator),
+        }
+    }
+
+    fn is_disjunctive_normal_form(evaluator: &Evaluator) -> bool {
+        match evaluator {
+            Evaluator::Or(left, right) => is_disjunctive_normal_form(left) && is_disjunctive_normal_form(right),
+            _ => is_term(evaluator),
+        }
+    }
+
+    /// Checks that the negation normal form of `formula` has the right shape and means the same.
+    fn assert_negation_normal_form(formula: &str) {
+        let original = Evaluator::new(formula);
+        let nnf = original.to_negation_normal_form();
+        assert!(is_negation_normal_form(&nnf), "{formula}: {nnf} is not in NNF");
+        assert_eq!(equivalent(&original, &nnf), Verdict::Proven, "{formula}: {nnf}");
+    }
+
+    /// Checks that the conjunctive normal form of `formula` has the right shape and means the same.
+    fn assert_conjunctive_normal_form(formula: &str) {
+        let original = Evaluator::new(formula);
+        let cnf = original.to_conjunctive_normal_form();
+        assert!(is_conjunctive_normal_form(&cnf), "{formula}: {cnf} is not in CNF");
+        assert_eq!(equivalent(&original, &cnf), Verdict::Proven, "{formula}: {cnf}");
+    }
+
     #[test]
     fn test_negation_normal_form_and_not() {
-        let formula = Evaluator::new("AB&!");
-        let nnf = formula.to_negation_normal_form();
-        assert_eq!(nnf.to_string(), "A!B!|");
+        assert_negation_normal_form("AB&!");
     }
 
     #[test]
     fn test_negation_normal_form_or_not() {
-        let formula = Evaluator::new("AB|!");
-        let nnf = formula.to_negation_normal_form();
-        assert_eq!(nnf.to_string(), "A!B!&");
+        assert_negation_normal_form("AB|!");
     }
 
     #[test]
     fn test_negation_normal_form_conditional() {
-        let formula = Evaluator::new("AB>");
-        let nnf = formula.to_negation_normal_form();
-        assert_eq!(nnf.to_string(), "A!B|");
+        assert_negation_normal_form("AB>");
+        assert_negation_normal_form("AB>!");
     }
 
     #[test]
     fn test_negation_normal_form_equivalence() {
-        let formula = Evaluator::new("AB=");
-        let nnf = formula.to_negation_normal_form();
-        assert_eq!(nnf.to_string(), "A!B|B!A|&"); 
-        // "AB&A!B!&|" is the same as "A!B|B!A|&" 
-        // because both are equivalent to AB=
-        // In logic notation, A ≡ B is equivalent to (A ∧ B) ∨ (¬A ∧ ¬B) (example from subject)
-        // and A ≡ B is also equivalent to (A → B) ∧ (B → A) (result from the test before Conditional is removed)
+        assert_negation_normal_form("AB=");
+        assert_negation_normal_form("AB=!");
     }
 
     #[test]
     fn test_negation_normal_form_or_and_not() {
-        let formula = Evaluator::new("AB|C&!");
-        let nnf = formula.to_negation_normal_form();
-        assert_eq!(nnf.to_string(), "A!B!&C!|");
+        assert_negation_normal_form("AB|C&!");
     }
 
     #[test]
     fn test_conjunctive_normal_form_and_not() {
-        let formula = Evaluator::new("AB&!");
-        let cnf = formula.to_conjunctive_normal_form();
-        assert_eq!(cnf.to_string(), "A!B!|");
+        assert_conjunctive_normal_form("AB&!");
     }
 
     #[test]
     fn test_conjunctive_normal_form_or_not() {
-        let formula = Evaluator::new("AB|!");
-        let cnf = formula.to_conjunctive_normal_form();
-        assert_eq!(cnf.to_string(), "A!B!&");
+        assert_conjunctive_normal_form("AB|!");
     }
 
     #[test]
     fn test_conjunctive_normal_form_or_and() {
-        let formula = Evaluator::new("AB|C&");
-        let cnf = formula.to_conjunctive_normal_form();
-        assert_eq!(cnf.to_string(), "AB|C&");
+        assert_conjunctive_normal_form("AB|C&");
+        assert_conjunctive_normal_form("AB&C|");
     }
 
     #[test]
     fn test_conjunctive_normal_form_multiple_or() {
-        let formula = Evaluator::new("AB|C|D|");
-        let cnf = formula.to_conjunctive_normal_form();
-        assert_eq!(cnf.to_string(), "ABCD|||");
+        assert_conjunctive_normal_form("AB|C|D|");
     }
 
     #[test]
     fn test_conjunctive_normal_form_multiple_and() {
-        let formula = Evaluator::new("AB&C&D&");
-        let cnf = formula.to_conjunctive_normal_form();
-        assert_eq!(cnf.to_string(), "ABCD&&&");
+        assert_conjunctive_normal_form("AB&C&D&");
     }
 
     #[test]
     fn test_conjunctive_normal_form_and_not_or() {
-        let formula = Evaluator::new("AB&!C!|");
-        let cnf = formula.to_conjunctive_normal_form();
-        assert_eq!(cnf.to_string(), "A!B!C!||");
+        assert_conjunctive_normal_form("AB&!C!|");
     }
 
     #[test]
     fn test_conjunctive_normal_form_or_not_and() {
-        let formula = Evaluator::new("AB|!C!&");
-        let cnf = formula.to_conjunctive_normal_form();
-        assert_eq!(cnf.to_string(), "A!B!C!&&");
+        assert_conjunctive_normal_form("AB|!C!&");
+    }
+
+    #[test]
+    fn test_conjunctive_normal_form_extended_operators() {
+        for formula in ["AB^", "AB=C>", "AB/CD#%", "ABC?E<", "AB&CD&|EF&|"] {
+            assert_conjunctive_normal_form(formula);
+        }
+    }
+
+    /// Checks that the disjunctive normal form of `formula` has the right shape and means the same.
+    fn assert_disjunctive_normal_form(formula: &str) {
+        let original = Evaluator::new(formula);
+        let dnf = original.to_disjunctive_normal_form();
+        assert!(is_disjunctive_normal_form(&dnf), "{formula}: {dnf} is not in DNF");
+        assert_eq!(equivalent(&original, &dnf), Verdict::Proven, "{formula}: {dnf}");
+    }
+
+    #[test]
+    fn test_disjunctive_normal_form() {
+        assert_eq!(Evaluator::new("AB|C&").to_disjunctive_normal_form().to_string(), "AC&BC&|");
+        assert_eq!(Evaluator::new("AB|!C|").to_disjunctive_normal_form().to_string(), "A!B!&C|");
+        assert_eq!(Evaluator::new("AB|CD|&").to_disjunctive_normal_form().to_string(), "AC&AD&BC&BD&|||");
+        for formula in ["AB^", "AB=C>", "AB/CD#%", "ABC?E<", "AB|CD|&EF|&", "AB>!C1&|"] {
+            assert_disjunctive_normal_form(formula);
+        }
+    }
+
+    #[test]
+    fn test_disjunctive_normal_form_removes_subsumed_terms() {
+        assert_eq!(Evaluator::new("AAB&|").to_disjunctive_normal_form().to_string(), "A");
+        assert_eq!(Evaluator::new("AB|A&").to_disjunctive_normal_form().to_string(), "A");
+        assert_eq!(Evaluator::new("AB&BA&|C|").to_disjunctive_normal_form().to_string(), "AB&C|");
+        assert_eq!(Evaluator::new("AA!&B&C|").to_disjunctive_normal_form().to_string(), "C");
+        assert_eq!(Evaluator::new("AA&B!A&&").to_disjunctive_normal_form().to_string(), "AB!&");
+        assert_eq!(Evaluator::new("AA!&").to_disjunctive_normal_form(), Evaluator::False);
+        // Subsumption does not merge terms that differ in one literal.
+        assert_eq!(Evaluator::new("AA!|B&").to_disjunctive_normal_form().to_string(), "AB&A!B&|");
+        assert_eq!(Evaluator::new("A1|").to_disjunctive_normal_form(), Evaluator::True);
+    }
+
+    #[test]
+    fn test_canonical_disjunctive_normal_form() {
+        assert_eq!(Evaluator::new("AB|").to_canonical_disjunctive_normal_form().unwrap().to_string(), "A!B&AB!&AB&||");
+        assert_eq!(Evaluator::new("AA!&B|").to_canonical_disjunctive_normal_form().unwrap().to_string(), "A!B&AB&|");
+        assert_eq!(Evaluator::new("AA!&").to_canonical_disjunctive_normal_form(), Ok(Evaluator::False));
+        assert_eq!(Evaluator::new("1").to_canonical_disjunctive_normal_form(), Ok(Evaluator::True));
+        let formula = Evaluator::new("AB/CD#%EEF<?");
+        let canonical = formula.to_canonical_disjunctive_normal_form().unwrap();
+        assert!(is_disjunctive_normal_form(&canonical));
+        assert_eq!(equivalent(&formula, &canonical), Verdict::Proven);
+        let names = (0..25).map(|i| format!("x{i:02}")).collect::<Vec<String>>();
+        assert_eq!(
+            Evaluator::new(&(names.join(" ") + &" |".repeat(24))).to_canonical_disjunctive_normal_form(),
+            Err(FormulaError::TooManyVariables { count: 25, limit: 24 })
+        );
     }
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+use crate::error::FormulaError;
+use crate::operation::operator::Operator;
+use crate::operation::truth_table::MAX_VARIABLES;
+
+use super::boolean_evaluator::Evaluator;
+use super::variables::VariableTable;
+
+/// Words where lane `l` holds bit `k` of `l`, for the six row bits that vary inside a word.
+const LANE_PATTERNS: [u64; 6] = [
+    0xAAAA_AAAA_AAAA_AAAA,
+    0xCCCC_CCCC_CCCC_CCCC,
+    0xF0F0_F0F0_F0F0_F0F0,
+    0xFF00_FF00_FF00_FF00,
+    0xFFFF_0000_FFFF_0000,
+    0xFFFF_FFFF_0000_0000,
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Instruction {
+    Constant(bool),
+    Variable(usize),
+    Apply(Operator),
+}
+
+/// Formula compiled to a postfix program over 64-bit words, evaluating 64 rows of its
+/// truth table per bitwise operation.
+///
+/// Rows follow the truth table numbering: the first variable of the table is the most
+/// significant bit of the row number, and lane `l` of block `b` is row `64 * b + l`.
+#[derive(Debug, Clone)]
+pub struct CompiledEvaluator {
+    variable_count: usize,
+    program: Vec<Instruction>,
+}
+
+impl CompiledEvaluator {
+    /// Compiles `evaluator` against `variables`, which must contain all of its variables
+    /// and at most `MAX_VARIABLES` names.
+    pub fn new(evaluator: &Evaluator, variables: &VariableTable) -> Result<Self, FormulaError> {
+        fn compile(
+            evaluator: &Evaluator,
+            variables: &VariableTable,
+            program: &mut Vec<Instruction>,
+        ) -> Result<(), FormulaError> {
+            match evaluator {
+                Evaluator::True => program.push(Instruction::Constant(true)),
+                Evaluator::False => program.push(Instruction::Constant(false)),
+                Evaluator::Variable(name) => {
+                    let index = variables
+                        .index(name)
+                        .ok_or_else(|| FormulaError::UnassignedVariable { variable: name.clone() })?;
+                    program.push(Instruction::Variable(index));
+                }
+                _ => {
+                    let (operator, operands) = evaluator.operator().unwrap();
+                    for operand in operands {
+                        compile(operand, variables, program)?;
+                    }
+                    program.push(Instruction::Apply(operator));
+                }
+            }
+            Ok(())
+        }
+
+        if variables.len() > MAX_VARIABLES {
+            return Err(FormulaError::TooManyVariables { count: variables.len(), limit: MAX_VARIABLES });
+        }
+        let mut program = vec![];
+        compile(evaluator, variables, &mut program)?;
+        Ok(CompiledEvaluator { variable_count: variables.len(), program })
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.variable_count
+    }
+
+    /// Number of rows of the truth table, `2ⁿ`.
+    pub fn row_count(&self) -> u64 {
+        1 << self.variable_count
+    }
+
+    /// Number of words needed to hold one bit per row.
+    pub fn block_count(&self) -> u64 {
+        self.row_count().div_ceil(64)
+    }
+
+    /// Assignments of `variable` for the 64 rows of `block`.
+    fn pattern(&self, variable: usize, block: u64) -> u64 {
+        let bit = self.variable_count - 1 - variable;
+        match LANE_PATTERNS.get(bit) {
+            Some(pattern) => *pattern,
+            None if block & (1 << (bit - 6)) != 0 => !0,
+            None => 0,
+        }
+    }
+
+    /// Outputs of rows `64 * block .. 64 * block + 64`, one per bit. Lanes past the last
+    /// row of a table with fewer than 64 rows are zero.
+    pub fn eval_block(&self, block: u64) -> u64 {
+        let mut stack: Vec<u64> = Vec::with_capacity(self.program.len());
+        for instruction in &self.program {
+            match *instruction {
+                Instruction::Constant(value) => stack.push(if value { !0 } else { 0 }),
+                Instruction::Variable(index) => stack.push(self.pattern(index, block)),
+                Instruction::Apply(operator) => {
+                    // The operands are the top of the stack; their result replaces them.
+                    let base = stack.len() - operator.arity();
+                    let word = operator.apply_bitwise(&stack[base..]);
+                    stack.truncate(base);
+                    stack.push(word);
+                }
+            }
+        }
+        let word = stack.pop().expect("a compiled formula leaves one word");
+        if self.row_count() < 64 {
+            word & ((1 << self.row_count()) - 1)
+        } else {
+            word
+        }
+    }
+
+    /// Output column of the whole truth table, packed 64 rows per word.
+    pub fn output_column(&self) -> Vec<u64> {
+        (0..self.block_count()).map(|block| self.eval_block(block)).collect()
+    }
+
+    /// Whether any row evaluates to true, stopping at the first block that has one.
+    pub fn is_satisfiable(&self) -> bool {
+        (0..self.block_count()).any(|block| self.eval_block(block) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boolean_evaluator::assignment::Bitmask;
+
+    #[test]
+    fn test_blocks_match_eval() {
+        for formula in ["AB&C|", "ABCDEFG??H^^^", "A B C D E F G H & & & & & & & !", "AB/CD#%EEF<?", "A1^0|"] {
+            let evaluator = Evaluator::new(formula);
+            let variables = evaluator.variable_table();
+            let compiled = CompiledEvaluator::new(&evaluator, &variables).unwrap();
+            let column = compiled.output_column();
+            for row in 0..compiled.row_count() {
+                // A `Bitmask` holds variable `j` in bit `j`, the row in bit `count - 1 - j`.
+                let count = variables.len();
+                let bits = (0..count).fold(0, |bits, j| bits | (row >> (count - 1 - j) & 1) << j);
+                let expected = evaluator.eval(&Bitmask::new(&variables, bits)).unwrap();
+                assert_eq!(column[(row / 64) as usize] >> (row % 64) & 1 == 1, expected, "{formula} row {row}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_tables_are_masked() {
+        let evaluator = Evaluator::new("A!");
+        let compiled = CompiledEvaluator::new(&evaluator, &evaluator.variable_table()).unwrap();
+        assert_eq!(compiled.output_column(), vec![0b01]);
+        assert!(compiled.is_satisfiable());
+        assert!(!CompiledEvaluator::new(&Evaluator::new("AA!&"), &evaluator.variable_table())
+            .unwrap()
+            .is_satisfiable());
+    }
+
+    #[test]
+    fn test_unknown_variable() {
+        let variables = VariableTable::from_iter(["A"]);
+        assert_eq!(
+            CompiledEvaluator::new(&Evaluator::new("AB&"), &variables).unwrap_err(),
+            FormulaError::UnassignedVariable { variable: "B".to_string() }
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use crate::error::FormulaError;
+use crate::operation::operator::{Associativity, Operator, OPERATORS};
+
+use super::boolean_evaluator::Evaluator;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Constant(bool),
+    Variable,
+    Operator(Operator),
+    Colon,
+    Open,
+    Close,
+}
+
+/// Longest operator spelling, ASCII or Unicode, starting at `chars[i]`.
+fn match_operator(chars: &[char], i: usize) -> Option<(Operator, usize)> {
+    OPERATORS
+        .iter()
+        .flat_map(|info| [(info.operator, info.infix), (info.operator, info.unicode)])
+        .filter(|(_, spelling)| {
+            let spelling = spelling.chars().collect::<Vec<char>>();
+            chars[i..].starts_with(&spelling)
+        })
+        .map(|(operator, spelling)| (operator, spelling.chars().count()))
+        .max_by_key(|(_, len)| *len)
+}
+
+fn tokenize(formula: &str) -> Result<Vec<(usize, Token, String)>, FormulaError> {
+    let chars = formula.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (token, len) = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let len = chars[i..].iter().take_while(|c| c.is_ascii_alphanumeric() || **c == '_').count();
+                (Token::Variable, len)
+            }
+            c if c.is_ascii_digit() => {
+                let len = chars[i..].iter().take_while(|c| c.is_ascii_alphanumeric() || **c == '_').count();
+                match len {
+                    1 if c == '0' || c == '1' => (Token::Constant(c == '1'), 1),
+                    _ => {
+                        let token = chars[i..i + len].iter().collect();
+                        return Err(FormulaError::InvalidCharacter { position: i, token });
+                    }
+                }
+            }
+            ':' => (Token::Colon, 1),
+            '(' => (Token::Open, 1),
+            ')' => (Token::Close, 1),
+            c => match match_operator(&chars, i) {
+                Some((operator, len)) => (Token::Operator(operator), len),
+                None => return Err(FormulaError::InvalidCharacter { position: i, token: c.to_string() }),
+            },
+        };
+        tokens.push((i, token, chars[i..i + len].iter().collect()));
+        i += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token, String)>,
+    next: usize,
+    /// Positions of the `(` whose group is being parsed, innermost last.
+    open: Vec<usize>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.next).map(|(_, token, _)| *token)
+    }
+
+    fn unexpected(&self) -> FormulaError {
+        let (position, _, token) = &self.tokens[self.next];
+        FormulaError::UnexpectedToken { position: *position, token: token.clone() }
+    }
+
+    /// Error for an operand expected at the end of the input, blamed on the innermost
+    /// unclosed `(` if any, otherwise on the last operator.
+    fn missing_operand(&self) -> FormulaError {
+        if let Some(&position) = self.open.last() {
+            return FormulaError::UnbalancedParenthesis { position };
+        }
+        match self.tokens.last() {
+            Some((position, _, token)) => FormulaError::MissingOperand { position: *position, token: token.clone() },
+            None => FormulaError::EmptyFormula,
+        }
+    }
+
+    fn parse_ternary(&mut self) -> Result<Evaluator, FormulaError> {
+        let condition = self.parse_binary(Operator::IfThenElse.info().precedence + 1)?;
+        if self.peek() != Some(Token::Operator(Operator::IfThenElse)) {
+            return Ok(condition);
+        }
+        let (position, _, token) = self.tokens[self.next].clone();
+        self.next += 1;
+        let then = self.parse_ternary()?;
+        match self.peek() {
+            Some(Token::Colon) => self.next += 1,
+            Some(_) => return Err(self.unexpected()),
+            None if self.open.is_empty() => return Err(FormulaError::MissingOperand { position, token }),
+            None => return Err(self.missing_operand()),
+        }
+        let otherwise = self.parse_ternary()?;
+        Ok(Evaluator::IfThenElse(Box::new(condition), Box::new(then), Box::new(otherwise)))
+    }
+
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Evaluator, FormulaError> {
+        let mut left = self.parse_operand()?;
+
+        while let Some(Token::Operator(operator)) = self.peek() {
+            let info = operator.info();
+            if info.arity != 2 || info.precedence < min_precedence {
+                break;
+            }
+            self.next += 1;
+            let next_precedence = match info.associativity {
+                Associativity::Right => info.precedence,
+                Associativity::Left => info.precedence + 1,
+            };
+            let right = self.parse_binary(next_precedence)?;
+            left = Evaluator::from_operator(operator, vec![left, right]);
+        }
+        Ok(left)
+    }
+
+    fn parse_operand(&mut self) -> Result<Evaluator, FormulaError> {
+        match self.peek() {
+            Some(Token::Constant(value)) => {
+                self.next += 1;
+                Ok(if value { Evaluator::True } else { Evaluator::False })
+            }
+            Some(Token::Variable) => {
+                let name = self.tokens[self.next].2.clone();
+                self.next += 1;
+                Ok(Evaluator::Variable(name))
+            }
+            Some(Token::Operator(operator)) if operator.arity() == 1 => {
+                self.next += 1;
+                Ok(Evaluator::from_operator(operator, vec![self.parse_operand()?]))
+            }
+            Some(Token::Open) => {
+                let position = self.tokens[self.next].0;
+                self.next += 1;
+                self.open.push(position);
+                let inner = self.parse_ternary()?;
+                self.open.pop();
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.next += 1;
+                        Ok(inner)
+                    }
+                    Some(_) => Err(self.unexpected()),
+                    None => Err(FormulaError::UnbalancedParenthesis { position }),
+                }
+            }
+            Some(_) => Err(self.unexpected()),
+            None => Err(self.missing_operand()),
+        }
+    }
+}
+
+impl Evaluator {
+    /// Parses an infix formula such as `A & B | !C` or `(door_open -> x17) <-> alarm`.
+    ///
+    /// Identifiers are runs of letters, digits and underscores that do not start with a digit.
+    ///
+    /// Spellings, precedence and associativity come from the operator table. From the loosest to
+    /// the tightest binding operator:
+    ///
+    /// | Operator          | Meaning                   | Associativity |
+    /// |-------------------|---------------------------|---------------|
+    /// | `? :`             | if-then-else              | right         |
+    /// | `<->`             | equivalence               | left          |
+    /// | `<-`              | converse conditional      | left          |
+    /// | `->`              | conditional               | right         |
+    /// | `\|`, `!\|`       | disjunction, NOR          | left          |
+    /// | `^`, `!^`         | exclusive or, XNOR        | left          |
+    /// | `&`, `!&`         | conjunction, NAND         | left          |
+    /// | `!`               | negation                  | prefix        |
+    ///
+    /// Each operator may also be written with its Unicode spelling (`¬`, `∧`, `→`, ...).
+    /// Parentheses override precedence and whitespace is ignored.
+    pub fn from_infix(formula: &str) -> Result<Self, FormulaError> {
+        let mut parser = Parser { tokens: tokenize(formula)?, next: 0, open: vec![] };
+        let evaluator = parser.parse_ternary()?;
+        if parser.peek().is_some() {
+            return Err(parser.unexpected());
+        }
+        Ok(evaluator)
+    }
+
+    /// Prints the tree in infix notation with the fewest parentheses that keep
+    /// `from_infix` returning the same tree.
+    pub fn to_infix(&self) -> String {
+        self.infix(false)
+    }
+
+    /// Same as `to_infix`, with the Unicode spelling of every operator.
+    pub fn to_unicode(&self) -> String {
+        self.infix(true)
+    }
+
+    fn infix(&self, unicode: bool) -> String {
+        let (operator, operands) = match self {
+            Evaluator::True => return "1".to_string(),
+            Evaluator::False => return "0".to_string(),
+            Evaluator::Variable(name) => return name.clone(),
+            _ => self.operator().unwrap(),
+        };
+        let info = operator.info();
+        let spelling = if unicode { info.unicode } else { info.infix };
+        match operands[..] {
+            [operand] => format!("{spelling}{}", operand.infix_operand(info.precedence, false, unicode)),
+            [left, right] => {
+                let right_associative = info.associativity == Associativity::Right;
+                format!(
+                    "{} {spelling} {}",
+                    left.infix_operand(info.precedence, right_associative, unicode),
+                    right.infix_operand(info.precedence, !right_associative, unicode),
+                )
+            }
+            [condition, then, otherwise] => format!(
+                "{} {spelling} {} : {}",
+                condition.infix_operand(info.precedence, true, unicode),
+                then.infix(unicode),
+                otherwise.infix_operand(info.precedence, false, unicode),
+            ),
+            _ => unreachable!("operators take one to three operands"),
+        }
+    }
+
+    fn infix_precedence(&self) -> u8 {
+        match self.operator() {
+            Some((operator, _)) => operator.info().precedence,
+            None => Operator::Not.info().precedence,
+        }
+    }
+
+    /// Prints an operand, parenthesised when it binds looser than its parent,
+    /// or equally loose on the side the parent's associativity does not group.
+    fn infix_operand(&self, parent_precedence: u8, parenthesise_ties: bool, unicode: bool) -> String {
+        let precedence = self.infix_precedence();
+        if precedence < parent_precedence || (precedence == parent_precedence && parenthesise_ties) {
+            format!("({})", self.infix(unicode))
+        } else {
+            self.infix(unicode)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_infix_matches_rpn() {
+        let cases = [
+            ("A & B | C", "AB&C|"),
+            ("A | B & C", "ABC&|"),
+            ("!A & B", "A!B&"),
+            ("!(A & B)", "AB&!"),
+            ("A ^ B | C ^ D", "AB^CD^|"),
+            ("A -> B -> C", "ABC>>"),
+            ("A <-> B <-> C", "AB=C="),
+            ("A -> B <-> C", "AB>C="),
+            ("(A | B) & C", "AB|C&"),
+            ("A & 1 | !0", "A1&0!|"),
+            ("A !& B !| C", "AB/C#"),
+            ("A !^ !B & C", "AB!C&%"),
+            ("A <- B -> C", "ABC><"),
+            ("A ? B : C ? D : E", "ABCDE??"),
+            ("A & B ? C <-> D : !E", "AB&CD=E!?"),
+        ];
+        for (infix, rpn) in cases {
+            assert_eq!(Evaluator::from_infix(infix).unwrap(), Evaluator::new(rpn), "{infix}");
+        }
+    }
+
+    #[test]
+    fn test_to_infix_minimal_parentheses() {
+        assert_eq!(Evaluator::new("AB&C|").to_infix(), "A & B | C");
+        assert_eq!(Evaluator::new("AB|C&").to_infix(), "(A | B) & C");
+        assert_eq!(Evaluator::new("ABC&&").to_infix(), "A & (B & C)");
+        assert_eq!(Evaluator::new("AB&C&").to_infix(), "A & B & C");
+        assert_eq!(Evaluator::new("AB>C>").to_infix(), "(A -> B) -> C");
+        assert_eq!(Evaluator::new("ABC>>").to_infix(), "A -> B -> C");
+        assert_eq!(Evaluator::new("AB&!C!|").to_infix(), "!(A & B) | !C");
+        assert_eq!(Evaluator::new("door_open x17 ! &").to_infix(), "door_open & !x17");
+        assert_eq!(Evaluator::new("AB/C/").to_infix(), "A !& B !& C");
+        assert_eq!(Evaluator::new("ABC//").to_infix(), "A !& (B !& C)");
+        assert_eq!(Evaluator::new("ABC?DEF?G?").to_infix(), "(A ? B : C) ? D ? E : F : G");
+        assert_eq!(Evaluator::new("AB|CD&E?").to_infix(), "A | B ? C & D : E");
+    }
+
+    #[test]
+    fn test_infix_round_trip() {
+        for rpn in ["AB&C|", "AB=C>!", "ABC^^D|", "AB>!C=", "A!!B&", "AB<C#D%!", "ABC?DE?F&", "ABCDE??", "ABCD?E?"] {
+            let tree = Evaluator::new(rpn);
+            assert_eq!(Evaluator::from_infix(&tree.to_infix()).unwrap(), tree);
+        }
+    }
+
+    #[test]
+    fn test_unicode_spellings() {
+        let tree = Evaluator::new("AB&!C>DE#=");
+        assert_eq!(tree.to_unicode(), "¬(A ∧ B) → C ↔ D ↓ E");
+        assert_eq!(Evaluator::from_infix(&tree.to_unicode()).unwrap(), tree);
+        assert_eq!(Evaluator::from_infix("A ∨ ¬B ⊕ C").unwrap(), Evaluator::new("AB!C^|"));
+    }
+
+    #[test]
+    fn test_from_infix_identifiers() {
+        assert_eq!(
+            Evaluator::from_infix("door_open&!x17 -> _alarm").unwrap(),
+            Evaluator::new("door_open x17 ! & _alarm >")
+        );
+        assert_eq!(
+            Evaluator::from_infix("A & 17").unwrap_err(),
+            FormulaError::InvalidCharacter { position: 4, token: "17".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_from_infix_errors() {
+        assert_eq!(
+            Evaluator::from_infix("A &").unwrap_err(),
+            FormulaError::MissingOperand { position: 2, token: "&".to_string() }
+        );
+        assert_eq!(
+            Evaluator::from_infix("(A | B").unwrap_err(),
+            FormulaError::UnbalancedParenthesis { position: 0 }
+        );
+        assert_eq!(
+            Evaluator::from_infix("A & (").unwrap_err(),
+            FormulaError::UnbalancedParenthesis { position: 4 }
+        );
+        assert_eq!(
+            Evaluator::from_infix("(A | (B ? C").unwrap_err(),
+            FormulaError::UnbalancedParenthesis { position: 5 }
+        );
+        assert_eq!(
+            Evaluator::from_infix("A B").unwrap_err(),
+            FormulaError::UnexpectedToken { position: 2, token: "B".to_string() }
+        );
+        assert_eq!(
+            Evaluator::from_infix("A => B").unwrap_err(),
+            FormulaError::InvalidCharacter { position: 2, token: "=".to_string() }
+        );
+        assert_eq!(
+            Evaluator::from_infix("A ? B").unwrap_err(),
+            FormulaError::MissingOperand { position: 2, token: "?".to_string() }
+        );
+        assert_eq!(Evaluator::from_infix("  ").unwrap_err(), FormulaError::EmptyFormula);
+    }
+}
@@ -1 +1,7 @@
-pub mod boolean_evaluator;
\ No newline at end of file
+pub mod boolean_evaluator;
+pub mod assignment;
+pub mod compiled;
+pub mod infix;
+pub mod normal_form;
+pub mod quine_mccluskey;
+pub mod variables;
\ No newline at end of file
@@ -0,0 +1,361 @@
+use std::fmt;
+use std::ops::Not;
+
+use crate::operation::operator::Operator;
+use crate::operation::rpn::join_rpn;
+
+use super::boolean_evaluator::Evaluator;
+
+/// Variable or its negation, ordered by variable name and then positive first.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Literal {
+    pub variable: String,
+    pub negated: bool,
+}
+
+impl Literal {
+    pub fn positive(variable: &str) -> Self {
+        Literal { variable: variable.to_string(), negated: false }
+    }
+
+    pub fn negative(variable: &str) -> Self {
+        Literal { variable: variable.to_string(), negated: true }
+    }
+
+    pub fn to_evaluator(&self) -> Evaluator {
+        let variable = Evaluator::Variable(self.variable.clone());
+        if self.negated { Evaluator::Not(Box::new(variable)) } else { variable }
+    }
+
+    fn tokens(&self, out: &mut Vec<String>) {
+        out.push(self.variable.clone());
+        if self.negated {
+            out.push("!".to_string());
+        }
+    }
+}
+
+impl Not for Literal {
+    type Output = Literal;
+
+    fn not(self) -> Literal {
+        Literal { negated: !self.negated, ..self }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = vec![];
+        self.tokens(&mut tokens);
+        write!(f, "{}", join_rpn(&tokens))
+    }
+}
+
+/// Sorted set of literals: a disjunction as a clause of a `Cnf`, a conjunction as a term of a
+/// `Dnf`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Clause {
+    literals: Vec<Literal>,
+}
+
+impl Clause {
+    pub fn new(literals: impl IntoIterator<Item = Literal>) -> Self {
+        let mut literals = literals.into_iter().collect::<Vec<Literal>>();
+        literals.sort();
+        literals.dedup();
+        Clause { literals }
+    }
+
+    pub fn literals(&self) -> &[Literal] {
+        &self.literals
+    }
+
+    pub fn len(&self) -> usize {
+        self.literals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty()
+    }
+
+    /// Whether some variable occurs with both signs, which makes a clause always true and a
+    /// term always false.
+    pub fn has_complementary_literals(&self) -> bool {
+        // Sorting puts the two signs of a variable next to each other.
+        self.literals.windows(2).any(|pair| pair[0].variable == pair[1].variable)
+    }
+
+    /// Whether every literal of `self` is in `other`.
+    pub fn is_subset(&self, other: &Clause) -> bool {
+        self.literals.iter().all(|literal| other.literals.binary_search(literal).is_ok())
+    }
+
+    /// The literals joined by `operator`, or `empty` when there are none.
+    fn to_evaluator(&self, operator: Operator, empty: Evaluator) -> Evaluator {
+        join(self.literals.iter().map(Literal::to_evaluator), operator, empty)
+    }
+
+    fn tokens(&self, operator: Operator, empty: &str, out: &mut Vec<String>) {
+        if self.is_empty() {
+            return out.push(empty.to_string());
+        }
+        for literal in &self.literals {
+            literal.tokens(out);
+        }
+        out.extend(std::iter::repeat_n(operator.info().symbol.to_string(), self.len() - 1));
+    }
+}
+
+/// Conjunction of clauses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cnf {
+    clauses: Vec<Clause>,
+}
+
+impl Cnf {
+    pub fn new(clauses: Vec<Clause>) -> Self {
+        Cnf { clauses }
+    }
+
+    /// Clauses of `to_conjunctive_normal_form`, without tautological clauses.
+    pub fn from_evaluator(evaluator: &Evaluator) -> Self {
+        let cnf = evaluator.to_conjunctive_normal_form();
+        let clauses = chain(&cnf, Operator::And)
+            .into_iter()
+            .filter_map(|part| literals(part, Operator::Or))
+            .collect();
+        let mut cnf = Cnf { clauses };
+        cnf.remove_tautologies();
+        cnf
+    }
+
+    pub fn clauses(&self) -> &[Clause] {
+        &self.clauses
+    }
+
+    pub fn remove_tautologies(&mut self) {
+        self.clauses.retain(|clause| !clause.has_complementary_literals());
+    }
+
+    /// Drops clauses implied by a smaller one or repeating an earlier one.
+    pub fn remove_subsumed(&mut self) {
+        self.clauses = remove_subsumed(std::mem::take(&mut self.clauses));
+    }
+
+    /// `True` without clauses, `False` when a clause is empty.
+    pub fn to_evaluator(&self) -> Evaluator {
+        if self.clauses.iter().any(Clause::is_empty) {
+            return Evaluator::False;
+        }
+        let clauses = self.clauses.iter().map(|clause| clause.to_evaluator(Operator::Or, Evaluator::False));
+        join(clauses, Operator::And, Evaluator::True)
+    }
+}
+
+impl fmt::Display for Cnf {
+    /// Prints each clause with its `|` operators after its literals, then the `&` operators.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = vec![];
+        for clause in &self.clauses {
+            clause.tokens(Operator::Or, "0", &mut tokens);
+        }
+        write!(f, "{}", join_tokens(tokens, self.clauses.len(), Operator::And, "1"))
+    }
+}
+
+/// Disjunction of terms.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dnf {
+    terms: Vec<Clause>,
+}
+
+impl Dnf {
+    pub fn new(terms: Vec<Clause>) -> Self {
+        Dnf { terms }
+    }
+
+    /// Distributes AND over OR in the negation normal form of `evaluator`, dropping
+    /// contradictory and subsumed terms.
+    pub fn from_evaluator(evaluator: &Evaluator) -> Self {
+        let mut dnf = Dnf { terms: distribute(&evaluator.to_negation_normal_form()) };
+        dnf.remove_contradictions();
+        dnf.remove_subsumed();
+        dnf
+    }
+
+    pub fn terms(&self) -> &[Clause] {
+        &self.terms
+    }
+
+    pub fn remove_contradictions(&mut self) {
+        self.terms.retain(|term| !term.has_complementary_literals());
+    }
+
+    /// Drops terms implying a smaller one or repeating an earlier one.
+    pub fn remove_subsumed(&mut self) {
+        self.terms = remove_subsumed(std::mem::take(&mut self.terms));
+    }
+
+    /// `False` without terms, `True` when a term is empty.
+    pub fn to_evaluator(&self) -> Evaluator {
+        if self.terms.iter().any(Clause::is_empty) {
+            return Evaluator::True;
+        }
+        let terms = self.terms.iter().map(|term| term.to_evaluator(Operator::And, Evaluator::True));
+        join(terms, Operator::Or, Evaluator::False)
+    }
+}
+
+impl fmt::Display for Dnf {
+    /// Prints each term with its `&` operators after its literals, then the `|` operators.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = vec![];
+        for term in &self.terms {
+            term.tokens(Operator::And, "1", &mut tokens);
+        }
+        write!(f, "{}", join_tokens(tokens, self.terms.len(), Operator::Or, "0"))
+    }
+}
+
+/// Terms of a formula in negation normal form, conjunctions being distributed over disjunctions.
+fn distribute(evaluator: &Evaluator) -> Vec<Clause> {
+    match evaluator {
+        Evaluator::True => vec![Clause::default()],
+        Evaluator::False => vec![],
+        Evaluator::Or(left, right) => [distribute(left), distribute(right)].concat(),
+        Evaluator::And(left, right) => {
+            let right = distribute(right);
+            distribute(left)
+                .iter()
+                .flat_map(|left| {
+                    right.iter().map(|right| Clause::new(left.literals.iter().chain(&right.literals).cloned()))
+                })
+                .collect()
+        }
+        _ => literals(evaluator, Operator::And).into_iter().collect(),
+    }
+}
+
+/// Drops clauses that contain a smaller one or repeat an earlier one, keeping the order of the rest.
+fn remove_subsumed(clauses: Vec<Clause>) -> Vec<Clause> {
+    let absorbed = |index: usize| {
+        let clause = &clauses[index];
+        (0..clauses.len()).any(|other| {
+            other != index && clauses[other].is_subset(clause) && (clauses[other].len() < clause.len() || other < index)
+        })
+    };
+    let keep = (0..clauses.len()).map(|index| !absorbed(index)).collect::<Vec<bool>>();
+    clauses.into_iter().zip(keep).filter(|(_, keep)| *keep).map(|(clause, _)| clause).collect()
+}
+
+/// Operands of the chain of `operator` nodes at the root of `evaluator`, left to right.
+fn chain(evaluator: &Evaluator, operator: Operator) -> Vec<&Evaluator> {
+    let mut operands = vec![];
+    let mut pending = vec![evaluator];
+    while let Some(node) = pending.pop() {
+        match node.operator() {
+            Some((inner, nodes)) if inner == operator => pending.extend(nodes.into_iter().rev()),
+            _ => operands.push(node),
+        }
+    }
+    operands
+}
+
+/// Literals of a chain of `operator` over literals and constants, dropping the neutral
+/// constant, or `None` when the absorbing one decides the chain.
+fn literals(evaluator: &Evaluator, operator: Operator) -> Option<Clause> {
+    let absorbing = if operator == Operator::And { Evaluator::False } else { Evaluator::True };
+    let mut literals = vec![];
+    for operand in chain(evaluator, operator) {
+        match operand {
+            Evaluator::Variable(name) => literals.push(Literal::positive(name)),
+            Evaluator::Not(box Evaluator::Variable(name)) => literals.push(Literal::negative(name)),
+            Evaluator::True | Evaluator::False if *operand == absorbing => return None,
+            Evaluator::True | Evaluator::False => {}
+            _ => panic!("{operand} is not a literal"),
+        }
+    }
+    Some(Clause::new(literals))
+}
+
+/// Left-nested chain of `operator` over `operands`, or `empty` when there are none.
+fn join(operands: impl Iterator<Item = Evaluator>, operator: Operator, empty: Evaluator) -> Evaluator {
+    operands
+        .reduce(|left, right| Evaluator::from_operator(operator, vec![left, right]))
+        .unwrap_or(empty)
+}
+
+/// RPN of `count` operand token groups joined by `operator`, or `empty` without operands.
+fn join_tokens(mut tokens: Vec<String>, count: usize, operator: Operator, empty: &str) -> String {
+    if count == 0 {
+        return empty.to_string();
+    }
+    tokens.extend(std::iter::repeat_n(operator.info().symbol.to_string(), count - 1));
+    join_rpn(&tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clause_is_sorted_set() {
+        let clause = Clause::new([Literal::negative("B"), Literal::positive("A"), Literal::negative("B")]);
+        assert_eq!(clause.literals(), [Literal::positive("A"), Literal::negative("B")]);
+        assert_eq!(Cnf::new(vec![clause.clone()]).to_string(), "AB!|");
+        assert!(!clause.has_complementary_literals());
+        assert!(Clause::new([Literal::positive("A"), !Literal::positive("A")]).has_complementary_literals());
+        assert!(Clause::new([Literal::negative("B")]).is_subset(&clause));
+    }
+
+    #[test]
+    fn test_cnf_from_evaluator() {
+        let cnf = Cnf::from_evaluator(&Evaluator::new("AB&C|"));
+        assert_eq!(
+            cnf.clauses(),
+            [
+                Clause::new([Literal::positive("A"), Literal::positive("C")]),
+                Clause::new([Literal::positive("B"), Literal::positive("C")]),
+            ]
+        );
+        assert_eq!(cnf.to_string(), "AC|BC|&");
+        assert_eq!(cnf.to_evaluator(), Evaluator::new("AC|BC|&"));
+
+        // (A | !A) is dropped, repeated literals merge and clauses print with sorted literals.
+        let cnf = Cnf::from_evaluator(&Evaluator::new("AA!|CB|&BB|&"));
+        assert_eq!(cnf.to_string(), "BC|B&");
+        assert_eq!(Cnf::from_evaluator(&Evaluator::new("AA!|")).to_string(), "1");
+        let contradiction = Cnf::from_evaluator(&Evaluator::new("A0&"));
+        assert_eq!(contradiction.to_string(), "A0&");
+        assert_eq!(contradiction.to_evaluator(), Evaluator::False);
+        assert_eq!(Cnf::from_evaluator(&Evaluator::new("A0|")).to_string(), "A");
+    }
+
+    #[test]
+    fn test_cnf_remove_subsumed() {
+        let mut cnf = Cnf::from_evaluator(&Evaluator::new("AB|A&BA|&"));
+        assert_eq!(cnf.to_string(), "AB|AAB|&&");
+        cnf.remove_subsumed();
+        assert_eq!(cnf.to_string(), "A");
+    }
+
+    #[test]
+    fn test_dnf_from_evaluator() {
+        let dnf = Dnf::from_evaluator(&Evaluator::new("AB|C!&"));
+        assert_eq!(dnf.terms().len(), 2);
+        assert_eq!(dnf.to_string(), "AC!&BC!&|");
+        assert_eq!(dnf.to_evaluator(), Evaluator::new("AC!&BC!&|"));
+        assert_eq!(Dnf::from_evaluator(&Evaluator::new("AA!&")).to_string(), "0");
+        assert_eq!(Dnf::from_evaluator(&Evaluator::new("A1|")).to_evaluator(), Evaluator::True);
+        assert_eq!(Dnf::new(vec![Clause::default(), Clause::default()]).to_string(), "11|");
+    }
+
+    #[test]
+    fn test_printer_separates_identifiers() {
+        let dnf = Dnf::from_evaluator(&Evaluator::new("door_open alarm ! &"));
+        assert_eq!(dnf.to_string(), "alarm ! door_open &");
+        assert_eq!(Evaluator::new(&dnf.to_string()), dnf.to_evaluator());
+        assert_eq!(Literal::negative("x1").to_string(), "x1 !");
+        assert_eq!(Literal::negative("A").to_string(), "A!");
+    }
+}
@@ -0,0 +1,282 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::error::FormulaError;
+
+use super::boolean_evaluator::Evaluator;
+use super::compiled::CompiledEvaluator;
+use super::normal_form::{Clause, Dnf, Literal};
+use super::variables::VariableTable;
+
+/// Formulas with more variables are not minimised: the number of prime implicants can grow
+/// exponentially with it. This bounds the rows, not the choice among primes that no minterm
+/// forces, which `PETRICK_PRODUCT_LIMIT` bounds.
+pub const MINIMISE_VARIABLE_LIMIT: usize = 16;
+
+/// Petrick's method keeps at most this many candidate covers. Past it, the minterms the
+/// essential primes leave are covered greedily, and the result may have more terms than the
+/// smallest cover.
+pub const PETRICK_PRODUCT_LIMIT: usize = 1024;
+
+/// Product term over the rows of a truth table: the rows agreeing with `value` on every bit
+/// outside `mask`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Implicant {
+    value: u64,
+    mask: u64,
+}
+
+impl Implicant {
+    fn covers(self, row: u64) -> bool {
+        (row ^ self.value) & !self.mask == 0
+    }
+
+    /// Literals of the term, the first variable being the most significant row bit.
+    fn to_clause(self, variables: &[String]) -> Clause {
+        let count = variables.len();
+        let literals = variables.iter().enumerate().filter_map(|(index, name)| {
+            let bit = 1 << (count - 1 - index);
+            (self.mask & bit == 0).then(|| Literal { variable: name.clone(), negated: self.value & bit == 0 })
+        });
+        Clause::new(literals)
+    }
+}
+
+/// Smallest sum of products equivalent to `evaluator` wherever `dont_care` is false. Rows
+/// where `dont_care` holds may be covered or not, whichever gives fewer terms.
+pub fn minimise(evaluator: &Evaluator, dont_care: Option<&Evaluator>) -> Result<Dnf, FormulaError> {
+    let mut names = evaluator.variables();
+    names.extend(dont_care.map(Evaluator::variables).unwrap_or_default());
+    names.sort();
+    names.dedup();
+    if names.len() > MINIMISE_VARIABLE_LIMIT {
+        return Err(FormulaError::TooManyVariables { count: names.len(), limit: MINIMISE_VARIABLE_LIMIT });
+    }
+    let variables = names.iter().cloned().collect::<VariableTable>();
+    let column = |evaluator: &Evaluator| {
+        CompiledEvaluator::new(evaluator, &variables).map(|compiled| compiled.output_column())
+    };
+    let outputs = column(evaluator)?;
+    let free = match dont_care {
+        Some(dont_care) => column(dont_care)?,
+        None => vec![0; outputs.len()],
+    };
+    let rows = |words: &[u64]| {
+        (0..1u64 << names.len())
+            .filter(|&row| words[row as usize / 64] >> (row % 64) & 1 == 1)
+            .collect::<Vec<u64>>()
+    };
+    let care = outputs.iter().zip(&free).map(|(output, free)| output & !free).collect::<Vec<u64>>();
+    Ok(minimal_sum_of_products(&names, &rows(&care), &rows(&free)))
+}
+
+/// Quine–McCluskey minimisation of the function of `variables` that is true on the rows
+/// `minterms`, free on the rows `dont_cares` and false elsewhere, rows being numbered like
+/// truth table rows. Returns the cover with the fewest terms, then the fewest literals, unless
+/// `PETRICK_PRODUCT_LIMIT` is reached.
+pub fn minimal_sum_of_products(variables: &[String], minterms: &[u64], dont_cares: &[u64]) -> Dnf {
+    let primes = prime_implicants(minterms.iter().chain(dont_cares).copied());
+    let mut cover = minimum_cover(&primes, minterms, variables.len(), PETRICK_PRODUCT_LIMIT)
+        .into_iter()
+        .map(|index| primes[index].to_clause(variables))
+        .collect::<Vec<Clause>>();
+    cover.sort();
+    Dnf::new(cover)
+}
+
+/// Implicants that cannot be merged with another, found by repeatedly merging pairs of
+/// implicants with the same mask that differ in a single bit.
+fn prime_implicants(rows: impl Iterator<Item = u64>) -> Vec<Implicant> {
+    let mut current = rows.map(|value| Implicant { value, mask: 0 }).collect::<HashSet<Implicant>>();
+    let mut primes = BTreeSet::new();
+    while !current.is_empty() {
+        let mut merged = HashSet::new();
+        let mut next = HashSet::new();
+        for &implicant in &current {
+            // Each pair is met once, from its member with the bit clear.
+            let free_bits = !(implicant.value | implicant.mask);
+            for shift in 0..64 - free_bits.leading_zeros() {
+                let bit = 1u64 << shift;
+                let partner = Implicant { value: implicant.value | bit, mask: implicant.mask };
+                if free_bits & bit != 0 && current.contains(&partner) {
+                    next.insert(Implicant { value: implicant.value, mask: implicant.mask | bit });
+                    merged.insert(implicant);
+                    merged.insert(partner);
+                }
+            }
+        }
+        primes.extend(current.difference(&merged).copied());
+        current = next;
+    }
+    primes.into_iter().collect()
+}
+
+/// Indices of a smallest set of `primes` covering every minterm: the essential primes, then
+/// Petrick's method on the minterms they leave, or a greedy cover past `product_limit`
+/// candidate covers.
+fn minimum_cover(primes: &[Implicant], minterms: &[u64], variable_count: usize, product_limit: usize) -> Vec<usize> {
+    let covering = |row: u64| {
+        (0..primes.len()).filter(|&index| primes[index].covers(row)).collect::<BTreeSet<usize>>()
+    };
+    let mut chosen = BTreeSet::new();
+    for &row in minterms {
+        let covering = covering(row);
+        if covering.len() == 1 {
+            chosen.extend(covering);
+        }
+    }
+    let remaining = minterms
+        .iter()
+        .filter(|&&row| !chosen.iter().any(|&index| primes[index].covers(row)))
+        .map(|&row| covering(row))
+        .collect::<BTreeSet<BTreeSet<usize>>>();
+
+    let literals = |index: usize| variable_count - primes[index].mask.count_ones() as usize;
+    let best = petrick(&remaining, literals, product_limit).unwrap_or_else(|| greedy_cover(remaining, literals));
+    chosen.extend(best);
+    chosen.into_iter().collect()
+}
+
+/// Petrick's method: multiplies out the product over `sums` of the sum of the primes in each,
+/// keeping only products not absorbed by another, and returns the product with the fewest
+/// primes, then the fewest literals. `None` once more than `limit` products are kept.
+fn petrick(sums: &BTreeSet<BTreeSet<usize>>, literals: impl Fn(usize) -> usize, limit: usize) -> Option<BTreeSet<usize>> {
+    let mut products = vec![BTreeSet::new()];
+    for sum in sums {
+        let mut expanded: Vec<BTreeSet<usize>> = vec![];
+        for product in &products {
+            for &index in sum {
+                let mut product = product.clone();
+                product.insert(index);
+                expanded.push(product);
+            }
+        }
+        expanded.sort_by_key(BTreeSet::len);
+        products = vec![];
+        for product in expanded {
+            if !products.iter().any(|kept: &BTreeSet<usize>| kept.is_subset(&product)) {
+                products.push(product);
+            }
+        }
+        if products.len() > limit {
+            return None;
+        }
+    }
+    let cost = |product: &BTreeSet<usize>| (product.len(), product.iter().map(|&index| literals(index)).sum::<usize>());
+    products.into_iter().min_by_key(|product| (cost(product), product.clone()))
+}
+
+/// Repeatedly picks the prime in the most of `sums`, then with the fewest literals, until
+/// every sum has a picked prime.
+fn greedy_cover(mut sums: BTreeSet<BTreeSet<usize>>, literals: impl Fn(usize) -> usize) -> BTreeSet<usize> {
+    let mut chosen = BTreeSet::new();
+    while !sums.is_empty() {
+        let mut counts = BTreeMap::new();
+        for &index in sums.iter().flatten() {
+            *counts.entry(index).or_insert(0usize) += 1;
+        }
+        let (&index, _) = counts
+            .iter()
+            .min_by_key(|&(&index, &count)| (Reverse(count), literals(index), index))
+            .expect("every minterm is covered by some prime");
+        chosen.insert(index);
+        sums.retain(|sum| !sum.contains(&index));
+    }
+    chosen
+}
+
+impl Evaluator {
+    /// Smallest sum-of-products form of the formula, found by Quine–McCluskey minimisation.
+    pub fn to_minimal_disjunctive_normal_form(&self) -> Result<Self, FormulaError> {
+        Ok(minimise(self, None)?.to_evaluator())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::validity::{equivalent, Verdict};
+
+    fn names(count: usize) -> Vec<String> {
+        (0..count).map(|i| ((b'A' + i as u8) as char).to_string()).collect()
+    }
+
+    #[test]
+    fn test_minimise_formulas() {
+        assert_eq!(Evaluator::new("AB&AB!&|").to_minimal_disjunctive_normal_form().unwrap().to_string(), "A");
+        assert_eq!(Evaluator::new("AB^").to_minimal_disjunctive_normal_form().unwrap().to_string(), "AB!&A!B&|");
+        assert_eq!(Evaluator::new("AA!|").to_minimal_disjunctive_normal_form(), Ok(Evaluator::True));
+        assert_eq!(Evaluator::new("AA!&").to_minimal_disjunctive_normal_form(), Ok(Evaluator::False));
+        assert_eq!(minimise(&Evaluator::new("ABC&|AB!&|"), None).unwrap().to_string(), "ABC&|");
+        for formula in ["AB/CD#%EEF<?", "ABC?D^", "AB>BC>&CA>&", "AB|CD|&EF|&"] {
+            let evaluator = Evaluator::new(formula);
+            let minimal = evaluator.to_minimal_disjunctive_normal_form().unwrap();
+            assert_eq!(equivalent(&evaluator, &minimal), Verdict::Proven, "{formula}: {minimal}");
+            let canonical = Dnf::from_evaluator(&evaluator.to_canonical_disjunctive_normal_form().unwrap());
+            assert!(minimise(&evaluator, None).unwrap().terms().len() <= canonical.terms().len());
+        }
+    }
+
+    #[test]
+    fn test_dont_cares() {
+        // f = Σm(4, 8, 10, 11, 12, 15) + d(9, 14) is AB! + AC + BC!D!; without the don't-cares it
+        // takes longer terms.
+        let cover = minimal_sum_of_products(&names(4), &[4, 8, 10, 11, 12, 15], &[9, 14]);
+        assert_eq!(cover.to_string(), "AB!&AC&BC!D!&&||");
+        let without = minimal_sum_of_products(&names(4), &[4, 8, 10, 11, 12, 15], &[]);
+        assert_eq!(without.terms().len(), 3);
+        assert!(without.terms().iter().map(Clause::len).sum::<usize>() > 7);
+
+        // A BCD decimal digit above 4 is at least 5: rows 10 to 15 are never used.
+        let digit = Evaluator::from_infix("(A & !B & !C) | (!A & B & (C | D))").unwrap();
+        let unused = Evaluator::from_infix("A & (B | C)").unwrap();
+        assert_eq!(minimise(&digit, Some(&unused)).unwrap().to_string(), "ABC&BD&||");
+    }
+
+    #[test]
+    fn test_petrick_cyclic_cover() {
+        // Every minterm has two primes and none is essential; two covers of three terms exist.
+        let cover = minimal_sum_of_products(&names(3), &[0, 1, 2, 5, 6, 7], &[]);
+        assert_eq!(cover.terms().len(), 3);
+        assert!(cover.terms().iter().all(|term| term.len() == 2));
+        let table = cover.to_evaluator();
+        let expected = Evaluator::from_infix("!(A ^ B) | (B ^ C)").unwrap();
+        assert_eq!(equivalent(&table, &expected), Verdict::Proven);
+    }
+
+    #[test]
+    fn test_greedy_cover_past_product_limit() {
+        // Same cyclic function as above: with a single product allowed, the cover is greedy.
+        let minterms = [0, 1, 2, 5, 6, 7];
+        let primes = prime_implicants(minterms.iter().copied());
+        let cover = minimum_cover(&primes, &minterms, 3, 1);
+        assert!(minterms.iter().all(|&row| cover.iter().any(|&index| primes[index].covers(row))));
+        assert!(cover.len() <= 4);
+
+        // A random function of 10 variables has a large cyclic part.
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let minterms = (0..1u64 << 10)
+            .filter(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state.is_multiple_of(3)
+            })
+            .collect::<Vec<u64>>();
+        let cover = minimal_sum_of_products(&names(10), &minterms, &[]);
+        let column = CompiledEvaluator::new(&cover.to_evaluator(), &names(10).iter().collect::<VariableTable>())
+            .unwrap()
+            .output_column();
+        let rows = (0..1u64 << 10).filter(|&row| column[row as usize / 64] >> (row % 64) & 1 == 1).collect::<Vec<u64>>();
+        assert_eq!(rows, minterms);
+    }
+
+    #[test]
+    fn test_variable_limit() {
+        let chain = (0..17).map(|i| format!("x{i:02}")).collect::<Vec<String>>().join(" ") + &" |".repeat(16);
+        assert_eq!(
+            minimise(&Evaluator::new(&chain), None),
+            Err(FormulaError::TooManyVariables { count: 17, limit: MINIMISE_VARIABLE_LIMIT })
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+/// Interning table mapping variable names to dense indices `0..len()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VariableTable {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl VariableTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the index of `name`, assigning the next free one on first sight.
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.indices.get(name) {
+            return index;
+        }
+        let index = self.names.len();
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), index);
+        index
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+
+    /// Names in index order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for VariableTable {
+    fn from_iter<I: IntoIterator<Item = S>>(names: I) -> Self {
+        let mut table = VariableTable::new();
+        for name in names {
+            table.intern(name.as_ref());
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_is_dense_and_stable() {
+        let mut table = VariableTable::new();
+        assert_eq!(table.intern("door_open"), 0);
+        assert_eq!(table.intern("x17"), 1);
+        assert_eq!(table.intern("door_open"), 0);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.index("x17"), Some(1));
+        assert_eq!(table.index("y"), None);
+        assert_eq!(table.name(1), "x17");
+    }
+}
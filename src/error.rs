@@ -0,0 +1,131 @@
+use std::fmt;
+
+/// Error produced when a formula cannot be turned into a value or a tree.
+///
+/// Positions are character indices into the original formula, so they can be
+/// used directly to underline the offending token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormulaError {
+    /// A token outside of the accepted alphabet.
+    InvalidCharacter { position: usize, token: String },
+    /// An operator found fewer operands on the stack than its arity.
+    MissingOperand { position: usize, token: String },
+    /// The formula ended with more than one value left on the stack.
+    DanglingOperands { position: usize, count: usize },
+    /// A token appeared where the grammar does not allow it.
+    UnexpectedToken { position: usize, token: String },
+    /// An opening parenthesis was never closed.
+    UnbalancedParenthesis { position: usize },
+    /// The formula contains no token at all.
+    EmptyFormula,
+    /// A variable of the formula has no value in the assignment it is evaluated against.
+    UnassignedVariable { variable: String },
+    /// The formula has more variables than an exhaustive enumeration is allowed to cover.
+    TooManyVariables { count: usize, limit: usize },
+    /// A variable was requested by name but does not occur in the formula.
+    UnknownVariable { variable: String },
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormulaError::InvalidCharacter { position, token } => {
+                write!(f, "invalid character '{token}' at position {position}")
+            }
+            FormulaError::MissingOperand { position, token } => {
+                write!(f, "operator '{token}' at position {position} is missing an operand")
+            }
+            FormulaError::DanglingOperands { position, count } => {
+                write!(f, "{count} operands left without operator at position {position}")
+            }
+            FormulaError::UnexpectedToken { position, token } => {
+                write!(f, "unexpected token '{token}' at position {position}")
+            }
+            FormulaError::UnbalancedParenthesis { position } => {
+                write!(f, "parenthesis opened at position {position} is never closed")
+            }
+            FormulaError::EmptyFormula => write!(f, "empty formula"),
+            FormulaError::UnassignedVariable { variable } => {
+                write!(f, "variable '{variable}' has no assigned value")
+            }
+            FormulaError::TooManyVariables { count, limit } => {
+                write!(f, "{count} variables exceed the enumeration limit of {limit}")
+            }
+            FormulaError::UnknownVariable { variable } => {
+                write!(f, "variable '{variable}' does not occur in the formula")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+/// Error produced when DIMACS input cannot be read. Lines are numbered from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DimacsError {
+    /// A clause appeared before any `p` line.
+    MissingHeader { line: usize },
+    /// A `p` line that is not `p <format> <variables> <clauses>`, or a second one.
+    InvalidHeader { line: usize },
+    /// A clause token that is not an integer.
+    InvalidLiteral { line: usize, token: String },
+    /// A WCNF clause whose weight is not a positive integer or `h`.
+    InvalidWeight { line: usize, token: String },
+    /// A literal whose variable exceeds the count declared in the header.
+    VariableOutOfRange { line: usize, variable: u64, limit: usize },
+    /// The last clause is not terminated by `0`.
+    UnterminatedClause { line: usize },
+    /// The number of clauses read differs from the header.
+    ClauseCountMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DimacsError::MissingHeader { line } => write!(f, "clause on line {line} precedes the 'p' header"),
+            DimacsError::InvalidHeader { line } => write!(f, "invalid or repeated header on line {line}"),
+            DimacsError::InvalidLiteral { line, token } => {
+                write!(f, "invalid literal '{token}' on line {line}")
+            }
+            DimacsError::InvalidWeight { line, token } => write!(f, "invalid weight '{token}' on line {line}"),
+            DimacsError::VariableOutOfRange { line, variable, limit } => {
+                write!(f, "variable {variable} on line {line} exceeds the {limit} declared variables")
+            }
+            DimacsError::UnterminatedClause { line } => {
+                write!(f, "clause ending on line {line} is not terminated by 0")
+            }
+            DimacsError::ClauseCountMismatch { expected, found } => {
+                write!(f, "header declares {expected} clauses but {found} were read")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DimacsError {}
+
+/// Error produced when a DRAT proof is malformed or does not refute its formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DratError {
+    /// The proof could not be decoded; `position` is a line number for text proofs and a
+    /// byte offset for binary ones.
+    Malformed { position: usize },
+    /// Lemma `step` (counted from 0) is neither RUP nor RAT with respect to the clauses before it.
+    InvalidLemma { step: usize, clause: Vec<i64> },
+    /// The proof never derives the empty clause and unit propagation finds no conflict.
+    NoRefutation,
+}
+
+impl fmt::Display for DratError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DratError::Malformed { position } => write!(f, "malformed proof at {position}"),
+            DratError::InvalidLemma { step, clause } => {
+                let lits = clause.iter().map(|lit| lit.to_string()).collect::<Vec<String>>().join(" ");
+                write!(f, "lemma {step} ({lits} 0) is neither RUP nor RAT")
+            }
+            DratError::NoRefutation => write!(f, "the proof does not refute the formula"),
+        }
+    }
+}
+
+impl std::error::Error for DratError {}
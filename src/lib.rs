@@ -1,6 +1,8 @@
 #![feature(box_patterns)]
 
+pub mod error;
 pub mod operation;
 pub mod utils;
 pub mod boolean_evaluator;
-pub mod sets;
\ No newline at end of file
+pub mod sets;
+pub mod solver;
\ No newline at end of file
@@ -1,7 +1,9 @@
 pub mod adder;
 pub mod multiplier;
 pub mod grey_code;
+pub mod operator;
 pub mod rpn;
+pub mod table_format;
 pub mod truth_table;
 
 mod operation_tests;
\ No newline at end of file
@@ -4,8 +4,10 @@ mod operator_tests {
         multiplier::multiplier,
         adder::adder,
         grey_code::grey_code,
-        rpn::rpn
+        rpn::{rpn, tokenize_rpn, try_rpn, try_rpn_format},
     };
+    use crate::boolean_evaluator::boolean_evaluator::Evaluator;
+    use crate::error::FormulaError;
 
     #[test]
     fn test_adder() {
@@ -27,4 +29,62 @@ mod operator_tests {
         assert_eq!(rpn("1011||="), true);
         assert_eq!(rpn("1011||=0>"), false);
     }
+
+    #[test]
+    fn test_rpn_surrounding_whitespace() {
+        assert_eq!(tokenize_rpn("AB&C|\n").unwrap(), tokenize_rpn("AB&C|").unwrap());
+        assert_eq!(Evaluator::new(" AB&C|\n"), Evaluator::new("AB&C|"));
+        assert_eq!(Evaluator::new("AB&C|\n").variables(), ["A", "B", "C"]);
+        assert_eq!(try_rpn_format("AB&C| "), Ok(()));
+        assert_eq!(rpn("1011||=\n"), rpn("1011||="));
+        assert_eq!(Evaluator::new("AB C|").variables(), ["AB", "C"]);
+    }
+
+    #[test]
+    fn test_rpn_not_and_conditional() {
+        assert!(!rpn("1!"));
+        assert!(rpn("0!"));
+        assert!(rpn("01>"));
+        assert!(rpn("00>"));
+        assert!(!rpn("10>"));
+    }
+
+    #[test]
+    fn test_rpn_extended_operators() {
+        assert!(!rpn("11/"));
+        assert!(rpn("10/"));
+        assert!(rpn("00#"));
+        assert!(!rpn("01#"));
+        assert!(rpn("00%"));
+        assert!(!rpn("01<"));
+        assert!(rpn("10<"));
+        assert!(rpn("110?"));
+        assert!(!rpn("010?"));
+        assert_eq!(try_rpn("10?"), Err(FormulaError::MissingOperand { position: 2, token: "?".to_string() }));
+    }
+
+    #[test]
+    fn test_try_rpn_errors() {
+        assert_eq!(
+            try_rpn("10&2|"),
+            Err(FormulaError::InvalidCharacter { position: 3, token: "2".to_string() })
+        );
+        assert_eq!(
+            try_rpn("1|"),
+            Err(FormulaError::MissingOperand { position: 1, token: "|".to_string() })
+        );
+        assert_eq!(try_rpn("101|"), Err(FormulaError::DanglingOperands { position: 4, count: 2 }));
+        assert_eq!(try_rpn(""), Err(FormulaError::EmptyFormula));
+    }
+
+    #[test]
+    fn test_try_rpn_format() {
+        assert_eq!(try_rpn_format("AB&C|"), Ok(()));
+        assert_eq!(try_rpn_format("AB&1|"), Ok(()));
+        assert_eq!(
+            try_rpn_format("AB&2|"),
+            Err(FormulaError::InvalidCharacter { position: 3, token: "2".to_string() })
+        );
+        assert_eq!(try_rpn_format("AB"), Err(FormulaError::DanglingOperands { position: 2, count: 2 }));
+    }
 }
\ No newline at end of file
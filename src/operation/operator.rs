@@ -0,0 +1,241 @@
+/// Logical operators understood by every front end of the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    Not,
+    And,
+    Or,
+    Xor,
+    Equivalence,
+    Conditional,
+    Nand,
+    Nor,
+    Xnor,
+    Converse,
+    IfThenElse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// Row of the operator table: how an operator is spelled, parsed and evaluated.
+#[derive(Debug, Clone, Copy)]
+pub struct OperatorInfo {
+    pub operator: Operator,
+    pub name: &'static str,
+    /// Single character used in RPN formulas.
+    pub symbol: char,
+    pub arity: usize,
+    /// ASCII spelling in infix formulas. The ternary operator is spelled `c ? t : e`.
+    pub infix: &'static str,
+    /// Unicode spelling in infix formulas, accepted by the parser as an alternative to `infix`.
+    pub unicode: &'static str,
+    /// Infix binding strength, higher binds tighter.
+    pub precedence: u8,
+    pub associativity: Associativity,
+    /// Truth function applied to exactly `arity` operands.
+    pub semantics: fn(&[bool]) -> bool,
+    /// Same truth function applied lane-wise to 64 assignments packed in each word.
+    pub bitwise: fn(&[u64]) -> u64,
+}
+
+pub const OPERATORS: [OperatorInfo; 11] = [
+    OperatorInfo {
+        operator: Operator::Not,
+        name: "not",
+        symbol: '!',
+        arity: 1,
+        infix: "!",
+        unicode: "¬",
+        precedence: 8,
+        associativity: Associativity::Right,
+        semantics: |v| !v[0],
+        bitwise: |w| !w[0],
+    },
+    OperatorInfo {
+        operator: Operator::And,
+        name: "and",
+        symbol: '&',
+        arity: 2,
+        infix: "&",
+        unicode: "∧",
+        precedence: 7,
+        associativity: Associativity::Left,
+        semantics: |v| v[0] & v[1],
+        bitwise: |w| w[0] & w[1],
+    },
+    OperatorInfo {
+        operator: Operator::Nand,
+        name: "nand",
+        symbol: '/',
+        arity: 2,
+        infix: "!&",
+        unicode: "↑",
+        precedence: 7,
+        associativity: Associativity::Left,
+        semantics: |v| !(v[0] & v[1]),
+        bitwise: |w| !(w[0] & w[1]),
+    },
+    OperatorInfo {
+        operator: Operator::Xor,
+        name: "xor",
+        symbol: '^',
+        arity: 2,
+        infix: "^",
+        unicode: "⊕",
+        precedence: 6,
+        associativity: Associativity::Left,
+        semantics: |v| v[0] ^ v[1],
+        bitwise: |w| w[0] ^ w[1],
+    },
+    OperatorInfo {
+        operator: Operator::Xnor,
+        name: "xnor",
+        symbol: '%',
+        arity: 2,
+        infix: "!^",
+        unicode: "⊙",
+        precedence: 6,
+        associativity: Associativity::Left,
+        semantics: |v| v[0] == v[1],
+        bitwise: |w| !(w[0] ^ w[1]),
+    },
+    OperatorInfo {
+        operator: Operator::Or,
+        name: "or",
+        symbol: '|',
+        arity: 2,
+        infix: "|",
+        unicode: "∨",
+        precedence: 5,
+        associativity: Associativity::Left,
+        semantics: |v| v[0] | v[1],
+        bitwise: |w| w[0] | w[1],
+    },
+    OperatorInfo {
+        operator: Operator::Nor,
+        name: "nor",
+        symbol: '#',
+        arity: 2,
+        infix: "!|",
+        unicode: "↓",
+        precedence: 5,
+        associativity: Associativity::Left,
+        semantics: |v| !(v[0] | v[1]),
+        bitwise: |w| !(w[0] | w[1]),
+    },
+    OperatorInfo {
+        operator: Operator::Conditional,
+        name: "conditional",
+        symbol: '>',
+        arity: 2,
+        infix: "->",
+        unicode: "→",
+        precedence: 4,
+        associativity: Associativity::Right,
+        semantics: |v| !v[0] | v[1],
+        bitwise: |w| !w[0] | w[1],
+    },
+    OperatorInfo {
+        operator: Operator::Converse,
+        name: "converse",
+        symbol: '<',
+        arity: 2,
+        infix: "<-",
+        unicode: "←",
+        precedence: 3,
+        associativity: Associativity::Left,
+        semantics: |v| v[0] | !v[1],
+        bitwise: |w| w[0] | !w[1],
+    },
+    OperatorInfo {
+        operator: Operator::Equivalence,
+        name: "equivalence",
+        symbol: '=',
+        arity: 2,
+        infix: "<->",
+        unicode: "↔",
+        precedence: 2,
+        associativity: Associativity::Left,
+        semantics: |v| v[0] == v[1],
+        bitwise: |w| !(w[0] ^ w[1]),
+    },
+    OperatorInfo {
+        operator: Operator::IfThenElse,
+        name: "if-then-else",
+        symbol: '?',
+        arity: 3,
+        infix: "?",
+        unicode: "?",
+        precedence: 1,
+        associativity: Associativity::Right,
+        semantics: |v| if v[0] { v[1] } else { v[2] },
+        bitwise: |w| (w[0] & w[1]) | (!w[0] & w[2]),
+    },
+];
+
+impl Operator {
+    pub fn info(self) -> &'static OperatorInfo {
+        OPERATORS
+            .iter()
+            .find(|info| info.operator == self)
+            .expect("every operator has a row in OPERATORS")
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Operator> {
+        OPERATORS.iter().find(|info| info.symbol == symbol).map(|info| info.operator)
+    }
+
+    pub fn arity(self) -> usize {
+        self.info().arity
+    }
+
+    pub fn apply(self, operands: &[bool]) -> bool {
+        (self.info().semantics)(operands)
+    }
+
+    pub fn apply_bitwise(self, operands: &[u64]) -> u64 {
+        (self.info().bitwise)(operands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_table_spellings_are_unique() {
+        let symbols = OPERATORS.iter().map(|info| info.symbol).collect::<HashSet<char>>();
+        let infix = OPERATORS.iter().map(|info| info.infix).collect::<HashSet<&str>>();
+        assert_eq!(symbols.len(), OPERATORS.len());
+        assert_eq!(infix.len(), OPERATORS.len());
+        for info in OPERATORS {
+            assert_eq!(info.operator.info().symbol, info.symbol);
+            assert_eq!(Operator::from_symbol(info.symbol), Some(info.operator));
+        }
+    }
+
+    #[test]
+    fn test_bitwise_matches_semantics() {
+        for info in OPERATORS {
+            for row in 0..1u64 << info.arity {
+                let values = (0..info.arity).map(|i| row & (1 << i) != 0).collect::<Vec<bool>>();
+                let words = values.iter().map(|v| if *v { !0 } else { 0 }).collect::<Vec<u64>>();
+                let expected = if (info.semantics)(&values) { !0 } else { 0 };
+                assert_eq!((info.bitwise)(&words), expected, "{} on {values:?}", info.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_semantics() {
+        assert!(Operator::Conditional.apply(&[false, false]));
+        assert!(!Operator::Converse.apply(&[false, true]));
+        assert!(Operator::IfThenElse.apply(&[false, false, true]));
+        assert!(!Operator::Nand.apply(&[true, true]));
+    }
+}
@@ -1,55 +1,123 @@
-pub fn rpn_format(formula: &str) {
-    if let Some(_) = formula.find(|c: char| !"!&|^>=".contains(c) && !c.is_ascii_uppercase()) {
-        panic!("Proposition contains incorrect characters!");
+use crate::error::FormulaError;
+
+use super::operator::Operator;
+
+/// Token of an RPN formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpnToken {
+    Constant(bool),
+    Variable(String),
+    Operator(Operator),
+}
+
+/// Splits an RPN formula into tokens, each paired with the character index it starts at.
+///
+/// A formula without inner whitespace uses the compact alphabet where every uppercase letter
+/// is a variable on its own (`AB&`). As soon as whitespace separates two tokens, identifiers are
+/// the maximal runs of letters, digits and underscores, so `door_open x17 & !` names two
+/// variables. Leading and trailing whitespace, such as the newline ending a line read from a
+/// file, does not change the mode. In both modes `0` and `1` are the constants.
+pub fn tokenize_rpn(formula: &str) -> Result<Vec<(usize, RpnToken)>, FormulaError> {
+    let chars = formula.chars().collect::<Vec<char>>();
+    let separated = formula.trim().contains(char::is_whitespace);
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if let Some(operator) = Operator::from_symbol(c) {
+            tokens.push((i, RpnToken::Operator(operator)));
+            i += 1;
+            continue;
+        }
+
+        let len = if separated {
+            chars[i..].iter().take_while(|c| c.is_ascii_alphanumeric() || **c == '_').count()
+        } else {
+            1
+        };
+        let word = chars[i..i + len].iter().collect::<String>();
+        let token = match word.as_str() {
+            "0" | "1" => RpnToken::Constant(word == "1"),
+            _ if !separated && c.is_ascii_uppercase() => RpnToken::Variable(word),
+            _ if separated && (c.is_ascii_alphabetic() || c == '_') => RpnToken::Variable(word),
+            _ => {
+                let token = if len == 0 { c.to_string() } else { word };
+                return Err(FormulaError::InvalidCharacter { position: i, token });
+            }
+        };
+        tokens.push((i, token));
+        i += len;
     }
+    Ok(tokens)
 }
 
-pub fn rpn(formula: &str) -> bool {
-    if let Some(_) = formula.find(|c| !"01!&|^>=".contains(c)) {
-        panic!("Proposition contains incorrect characters!");
+/// Joins tokens into a formula `tokenize_rpn` reads back the same: compact when every token is
+/// a single character of the compact alphabet, space-separated otherwise.
+pub fn join_rpn<S: AsRef<str>>(tokens: &[S]) -> String {
+    let compact = tokens.iter().map(AsRef::as_ref).all(|token| {
+        token.chars().count() == 1 && !token.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+    });
+    tokens.iter().map(AsRef::as_ref).collect::<Vec<&str>>().join(if compact { "" } else { " " })
+}
+
+/// Checks that `formula` is a well-formed RPN proposition over variables and constants.
+pub fn try_rpn_format(formula: &str) -> Result<(), FormulaError> {
+    let mut depth = 0usize;
+
+    for (position, token) in tokenize_rpn(formula)? {
+        match token {
+            RpnToken::Variable(_) | RpnToken::Constant(_) => depth += 1,
+            RpnToken::Operator(operator) if depth >= operator.arity() => depth -= operator.arity() - 1,
+            RpnToken::Operator(operator) => {
+                return Err(FormulaError::MissingOperand { position, token: operator.info().symbol.to_string() });
+            }
+        }
     }
-    formula
-        .chars()
-        .into_iter()
-        .fold(vec![], |mut acc, c| {
-            match c {
-                '0' | '1' => acc.push(if c == '1' {1} else {0}),
-                '!' => {
-                    let a = acc.pop().unwrap();
-                    acc.push(if a == 1 {1} else {0});
-                },
-                '&' => {
-                    let b = acc.pop().unwrap();
-                    let a = acc.pop().unwrap();
-                    acc.push( a & b );
-                },
-                '|' => {
-                    let b = acc.pop().unwrap();
-                    let a = acc.pop().unwrap();
-                    acc.push(a | b);
-                },
-                '^' => {
-                    let b = acc.pop().unwrap();
-                    let a = acc.pop().unwrap();
-                    acc.push(a ^ b);
-                },
-                '>' => {
-                    let b = acc.pop().unwrap();
-                    let a = acc.pop().unwrap();
-                    if a == 1 {
-                        acc.push(b);
-                    }
-                },
-                '=' => {
-                    let b = acc.pop().unwrap();
-                    let a = acc.pop().unwrap();
-                    acc.push(if a == b {1} else {0});
+
+    match depth {
+        0 => Err(FormulaError::EmptyFormula),
+        1 => Ok(()),
+        count => Err(FormulaError::DanglingOperands { position: formula.chars().count(), count }),
+    }
+}
+
+pub fn rpn_format(formula: &str) {
+    if let Err(e) = try_rpn_format(formula) {
+        panic!("Proposition is malformed: {e}");
+    }
+}
+
+/// Evaluates an RPN proposition made of the constants `0` and `1`.
+pub fn try_rpn(formula: &str) -> Result<bool, FormulaError> {
+    let mut stack: Vec<bool> = vec![];
+
+    for (position, token) in tokenize_rpn(formula)? {
+        match token {
+            RpnToken::Constant(value) => stack.push(value),
+            RpnToken::Variable(name) => return Err(FormulaError::InvalidCharacter { position, token: name }),
+            RpnToken::Operator(operator) => {
+                let arity = operator.arity();
+                if stack.len() < arity {
+                    return Err(FormulaError::MissingOperand { position, token: operator.info().symbol.to_string() });
                 }
-                _ => panic!("Proposition contains incorrect characters!"),
-            };
-
-            acc
-        })
-        .first()
-        .expect(&format!("Proposition contains syntax errors!\n Proposition: {formula}")) == &1
-}
\ No newline at end of file
+                let operands = stack.split_off(stack.len() - arity);
+                stack.push(operator.apply(&operands));
+            }
+        }
+    }
+
+    match stack.len() {
+        0 => Err(FormulaError::EmptyFormula),
+        1 => Ok(stack[0]),
+        count => Err(FormulaError::DanglingOperands { position: formula.chars().count(), count }),
+    }
+}
+
+pub fn rpn(formula: &str) -> bool {
+    try_rpn(formula).unwrap_or_else(|e| panic!("Proposition is malformed: {e}\n Proposition: {formula}"))
+}
@@ -0,0 +1,204 @@
+use std::io;
+
+use super::truth_table::TruthTable;
+
+/// Renders a `TruthTable` to a writer. Implement it to plug in a format of your own.
+pub trait TableRenderer {
+    fn render(&self, table: &TruthTable, out: &mut dyn io::Write) -> io::Result<()>;
+}
+
+/// Built-in renderers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    /// Pipe layout of `print_truth_table`, with the separator row Markdown requires.
+    Markdown,
+    Csv,
+    /// Array of row objects keyed by variable name, the output under `"="`.
+    Json,
+    /// `tabular` environment with the output column set apart.
+    Latex,
+    /// Output column as a single line of `0`s and `1`s in row order.
+    Bitstring,
+}
+
+impl TableFormat {
+    pub fn renderer(self) -> &'static dyn TableRenderer {
+        match self {
+            TableFormat::Markdown => &Markdown,
+            TableFormat::Csv => &Csv,
+            TableFormat::Json => &Json,
+            TableFormat::Latex => &Latex,
+            TableFormat::Bitstring => &Bitstring,
+        }
+    }
+}
+
+pub struct Markdown;
+pub struct Csv;
+pub struct Json;
+pub struct Latex;
+pub struct Bitstring;
+
+impl TableRenderer for Markdown {
+    fn render(&self, table: &TruthTable, out: &mut dyn io::Write) -> io::Result<()> {
+        for name in table.variables() {
+            write!(out, "| {name} ")?;
+        }
+        writeln!(out, "| = |")?;
+        for name in table.variables() {
+            write!(out, "|{}", "-".repeat(name.chars().count() + 2))?;
+        }
+        writeln!(out, "|---|")?;
+        for row in table.rows() {
+            for value in row.values {
+                write!(out, "| {} ", value as u8)?;
+            }
+            writeln!(out, "| {} |", row.output as u8)?;
+        }
+        Ok(())
+    }
+}
+
+impl TableRenderer for Csv {
+    fn render(&self, table: &TruthTable, out: &mut dyn io::Write) -> io::Result<()> {
+        for name in table.variables() {
+            write!(out, "{name},")?;
+        }
+        writeln!(out, "=")?;
+        for row in table.rows() {
+            for value in row.values {
+                write!(out, "{},", value as u8)?;
+            }
+            writeln!(out, "{}", row.output as u8)?;
+        }
+        Ok(())
+    }
+}
+
+impl TableRenderer for Json {
+    fn render(&self, table: &TruthTable, out: &mut dyn io::Write) -> io::Result<()> {
+        write!(out, "[")?;
+        for (i, row) in table.rows().enumerate() {
+            write!(out, "{}\n  {{", if i == 0 { "" } else { "," })?;
+            for (name, value) in table.variables().iter().zip(row.values) {
+                write!(out, "\"{name}\": {value}, ")?;
+            }
+            write!(out, "\"=\": {}}}", row.output)?;
+        }
+        // Every table has a row, if only the one of the empty assignment.
+        writeln!(out, "\n]")
+    }
+}
+
+impl TableRenderer for Latex {
+    fn render(&self, table: &TruthTable, out: &mut dyn io::Write) -> io::Result<()> {
+        writeln!(out, "\\begin{{tabular}}{{|{}|c|}}", "c|".repeat(table.variables().len()))?;
+        writeln!(out, "\\hline")?;
+        for name in table.variables() {
+            write!(out, "${}$ & ", name.replace('_', "\\_"))?;
+        }
+        writeln!(out, "$=$ \\\\")?;
+        writeln!(out, "\\hline")?;
+        for row in table.rows() {
+            for value in row.values {
+                write!(out, "{} & ", value as u8)?;
+            }
+            writeln!(out, "{} \\\\", row.output as u8)?;
+        }
+        writeln!(out, "\\hline")?;
+        writeln!(out, "\\end{{tabular}}")
+    }
+}
+
+impl TableRenderer for Bitstring {
+    fn render(&self, table: &TruthTable, out: &mut dyn io::Write) -> io::Result<()> {
+        let bits = table.outputs().map(|output| if output { '1' } else { '0' }).collect::<String>();
+        writeln!(out, "{bits}")
+    }
+}
+
+impl TruthTable {
+    /// Writes the table to `out` in one of the built-in formats.
+    pub fn write_to(&self, out: &mut dyn io::Write, format: TableFormat) -> io::Result<()> {
+        format.renderer().render(self, out)
+    }
+
+    /// Renders the table to a `String` in one of the built-in formats.
+    pub fn render(&self, format: TableFormat) -> String {
+        let mut out = vec![];
+        self.write_to(&mut out, format).expect("writing to a Vec cannot fail");
+        String::from_utf8(out).expect("renderers only write UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation::truth_table::truth_table;
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(
+            truth_table("AB|").unwrap().render(TableFormat::Markdown),
+            "| A | B | = |\n|---|---|---|\n| 0 | 0 | 0 |\n| 0 | 1 | 1 |\n| 1 | 0 | 1 |\n| 1 | 1 | 1 |\n"
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            truth_table("AB|").unwrap().render(TableFormat::Csv),
+            "A,B,=\n0,0,0\n0,1,1\n1,0,1\n1,1,1\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            truth_table("A B ^ !").unwrap().render(TableFormat::Json),
+            concat!(
+                "[\n",
+                "  {\"A\": false, \"B\": false, \"=\": true},\n",
+                "  {\"A\": false, \"B\": true, \"=\": false},\n",
+                "  {\"A\": true, \"B\": false, \"=\": false},\n",
+                "  {\"A\": true, \"B\": true, \"=\": true}\n",
+                "]\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_latex() {
+        assert_eq!(
+            truth_table("a_b c &").unwrap().render(TableFormat::Latex),
+            concat!(
+                "\\begin{tabular}{|c|c||c|}\n",
+                "\\hline\n",
+                "$a\\_b$ & $c$ & $=$ \\\\\n",
+                "\\hline\n",
+                "0 & 0 & 0 \\\\\n",
+                "0 & 1 & 0 \\\\\n",
+                "1 & 0 & 0 \\\\\n",
+                "1 & 1 & 1 \\\\\n",
+                "\\hline\n",
+                "\\end{tabular}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_bitstring_and_custom_renderer() {
+        struct Count;
+        impl TableRenderer for Count {
+            fn render(&self, table: &TruthTable, out: &mut dyn io::Write) -> io::Result<()> {
+                write!(out, "{}", table.minterms().len())
+            }
+        }
+
+        let table = truth_table("AB>").unwrap();
+        assert_eq!(table.render(TableFormat::Bitstring), "1101\n");
+        let mut out = vec![];
+        Count.render(&table, &mut out).unwrap();
+        assert_eq!(out, b"3");
+    }
+}
@@ -1,91 +1,186 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::BTreeMap;
+use std::fmt;
 
-use super::rpn::{
-    rpn,
-    rpn_format,
+use crate::boolean_evaluator::{
+    boolean_evaluator::Evaluator,
+    compiled::CompiledEvaluator,
+    variables::VariableTable,
 };
+use crate::error::FormulaError;
+use crate::solver::{cdcl, tseitin::Encoding};
 
-pub fn print_truth_table(formula: &str) {
-    let _ = truth_table(formula, true);
+
+/// Number of variables `TruthTable::new` accepts before refusing to enumerate.
+pub const DEFAULT_VARIABLE_LIMIT: usize = 24;
+
+/// Largest number of variables whose rows can be numbered by a `u64`.
+pub const MAX_VARIABLES: usize = 63;
+
+/// Truth table of a formula, with one row per assignment in increasing row number order.
+///
+/// A row number reads the assignment as a binary number whose most significant bit is
+/// the first variable, so row `i` over `A`, `B` assigns `A = i >> 1 & 1` and `B = i & 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruthTable {
+    variables: Vec<String>,
+    /// Output column packed 64 rows per word, as built by `CompiledEvaluator`.
+    outputs: Vec<u64>,
 }
 
-pub fn sat(formula: &str) -> bool {
-    truth_table(formula, false)
+/// One row of a `TruthTable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub index: usize,
+    /// Values of the table's variables, in the same order.
+    pub values: Vec<bool>,
+    pub output: bool,
 }
 
-fn truth_table(formula: &str, print_opt: bool) -> bool {
-    rpn_format(formula);
-
-    let mut letters = formula
-        .chars()
-        .filter(|c| c.is_ascii_uppercase())
-        .collect::<HashSet<char>>()
-        .into_iter()
-        .collect::<Vec<char>>();
-
-    // Forced to not chain the sort function, because it modifies the vector in place.
-    letters.sort();
-
-    let order = letters
-        .clone()
-        .into_iter()
-        .enumerate()
-        .collect::<HashMap<usize, char>>();
-
-    // Reverse the order so C corresponds to bit 1.
-    letters.reverse();
-    let letters = letters
-        .into_iter()
-        .enumerate()
-        .map(|(i, c)| (c, i))
-        .collect::<HashMap<char, usize>>();
-
-    if print_opt {
-        for i in 0..order.len() {
-            print!("| {} ", order[&i]);
+impl TruthTable {
+    /// Enumerates every assignment, refusing formulas with more than `DEFAULT_VARIABLE_LIMIT` variables.
+    pub fn new(evaluator: &Evaluator) -> Result<Self, FormulaError> {
+        Self::with_limit(evaluator, DEFAULT_VARIABLE_LIMIT)
+    }
+
+    /// Enumerates every assignment, refusing formulas with more than `limit` variables.
+    /// Limits above `MAX_VARIABLES` behave as `MAX_VARIABLES`.
+    pub fn with_limit(evaluator: &Evaluator, limit: usize) -> Result<Self, FormulaError> {
+        let table = evaluator.variable_table();
+        let count = table.len();
+        if count > limit.min(MAX_VARIABLES) {
+            return Err(FormulaError::TooManyVariables { count, limit: limit.min(MAX_VARIABLES) });
         }
-        print!("| = |\n");
+
+        let outputs = CompiledEvaluator::new(evaluator, &table)
+            .expect("every variable of the formula is in its own table")
+            .output_column();
+        Ok(TruthTable { variables: table.names().to_vec(), outputs })
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.variables
     }
 
-    let mut sat = false;
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        1 << self.variables.len()
+    }
 
-    let max_computation = letters.len().pow(2) - (if letters.len() % 2 == 0 { 0 } else { 1 });
-    for i in 0..max_computation {
+    /// Always false: a table without variables still has the row of the empty assignment.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
 
-        if print_opt {
-            for j in 0..order.len() {
-                if i & (1 << letters[&order[&j]]) != 0 {
-                    print!("| 1 ");
-                } else {
-                    print!("| 0 ");
-                }
-            }
+    pub fn output(&self, index: usize) -> bool {
+        assert!(index < self.len(), "row {index} is out of range");
+        self.outputs[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    /// Output column, in row order.
+    pub fn outputs(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len()).map(|index| self.output(index))
+    }
+
+    /// Output column packed 64 rows per word: bit `i % 64` of word `i / 64` is row `i`.
+    pub fn output_words(&self) -> &[u64] {
+        &self.outputs
+    }
+
+    pub fn row(&self, index: usize) -> Row {
+        let count = self.variables.len();
+        Row {
+            index,
+            values: (0..count).map(|j| index & (1 << (count - 1 - j)) != 0).collect(),
+            output: self.output(index),
         }
+    }
 
-        let new_formula = formula
-            .chars()
-            .map(|c| {
-                if letters.contains_key(&c) {
-                    if i & (1 << letters[&c]) != 0 {
-                        '1'
-                    } else {
-                        '0'
-                    }
-                } else {
-                    c
-                }
-            })
-            .collect::<String>();
-        
-        let res_rpn = rpn(&new_formula);
-        if res_rpn {
-            sat = true;
+    pub fn rows(&self) -> impl Iterator<Item = Row> + '_ {
+        (0..self.len()).map(|index| self.row(index))
+    }
+
+    /// Indices of the rows where the formula holds.
+    pub fn minterms(&self) -> Vec<usize> {
+        self.outputs().enumerate().filter(|(_, output)| *output).map(|(i, _)| i).collect()
+    }
+
+    /// Indices of the rows where the formula does not hold.
+    pub fn maxterms(&self) -> Vec<usize> {
+        self.outputs().enumerate().filter(|(_, output)| !*output).map(|(i, _)| i).collect()
+    }
+
+    pub fn is_satisfiable(&self) -> bool {
+        self.outputs.iter().any(|word| *word != 0)
+    }
+}
+
+impl fmt::Display for TruthTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in &self.variables {
+            write!(f, "| {name} ")?;
         }
-        if print_opt {
-            print!("| {} |\n", if res_rpn { 1 } else { 0 });
+        writeln!(f, "| = |")?;
+        for row in self.rows() {
+            for value in row.values {
+                write!(f, "| {} ", value as u8)?;
+            }
+            writeln!(f, "| {} |", row.output as u8)?;
         }
+        Ok(())
+    }
+}
+
+/// Builds the truth table of an RPN formula.
+pub fn truth_table(formula: &str) -> Result<TruthTable, FormulaError> {
+    TruthTable::new(&Evaluator::try_new(formula)?)
+}
+
+pub fn print_truth_table(formula: &str) {
+    match truth_table(formula) {
+        Ok(table) => print!("{table}"),
+        Err(e) => panic!("Cannot print the truth table of {formula}: {e}"),
+    }
+}
+
+/// Compiles an RPN formula over its own variables, refusing more than `DEFAULT_VARIABLE_LIMIT`.
+fn compile(formula: &str) -> Result<(Evaluator, VariableTable), FormulaError> {
+    let evaluator = Evaluator::try_new(formula)?;
+    let variables = evaluator.variable_table();
+    if variables.len() > DEFAULT_VARIABLE_LIMIT {
+        return Err(FormulaError::TooManyVariables { count: variables.len(), limit: DEFAULT_VARIABLE_LIMIT });
     }
-    sat
+    Ok((evaluator, variables))
+}
+
+/// Satisfying assignment of an RPN formula found by the CDCL solver on its Plaisted–Greenbaum
+/// encoding, or `None` when there is none.
+pub fn satisfying_assignment(formula: &str) -> Result<Option<BTreeMap<String, bool>>, FormulaError> {
+    let encoding = Encoding::plaisted_greenbaum(&Evaluator::try_new(formula)?);
+    Ok(cdcl::solve(encoding.formula()).model().map(|model| encoding.project(model)))
+}
+
+/// Decides an RPN formula with the CDCL solver, so it is not bound by the truth table limits.
+pub fn sat(formula: &str) -> bool {
+    match satisfying_assignment(formula) {
+        Ok(model) => model.is_some(),
+        Err(e) => panic!("Cannot decide {formula}: {e}"),
+    }
+}
+
+/// Whether two RPN formulas have the same truth table over the variables of either. Beyond
+/// the table limit, `solver::validity::equivalent` decides equivalence with the solver.
+pub fn tables_equal(left: &str, right: &str) -> Result<bool, FormulaError> {
+    let (left, mut variables) = compile(left)?;
+    let (right, _) = compile(right)?;
+    for name in right.variables() {
+        variables.intern(&name);
+    }
+    if variables.len() > DEFAULT_VARIABLE_LIMIT {
+        return Err(FormulaError::TooManyVariables { count: variables.len(), limit: DEFAULT_VARIABLE_LIMIT });
+    }
+    let left = CompiledEvaluator::new(&left, &variables)?;
+    let right = CompiledEvaluator::new(&right, &variables)?;
+    Ok((0..left.block_count()).all(|block| left.eval_block(block) == right.eval_block(block)))
 }
 
 #[cfg(test)]
@@ -112,4 +207,91 @@ mod tests {
     fn test_sat_xor() {
         assert_eq!(sat("AA^"), false);
     }
+
+    #[test]
+    fn test_truth_table_value() {
+        let table = truth_table("AB>").unwrap();
+        assert_eq!(table.variables(), ["A", "B"]);
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.outputs().collect::<Vec<bool>>(), vec![true, true, false, true]);
+        assert_eq!(table.row(2), Row { index: 2, values: vec![true, false], output: false });
+        assert_eq!(table.minterms(), vec![0, 1, 3]);
+        assert_eq!(table.maxterms(), vec![2]);
+        assert_eq!(table, truth_table("A!B|").unwrap());
+        assert_eq!(truth_table("A&").unwrap_err(), FormulaError::MissingOperand { position: 1, token: "&".to_string() });
+    }
+
+    #[test]
+    fn test_truth_table_display() {
+        let table = truth_table("AB&").unwrap();
+        assert_eq!(
+            table.to_string(),
+            "| A | B | = |\n| 0 | 0 | 0 |\n| 0 | 1 | 0 |\n| 1 | 0 | 0 |\n| 1 | 1 | 1 |\n"
+        );
+    }
+
+    #[test]
+    fn test_truth_table_row_count() {
+        for count in 1..=6 {
+            let formula = ["A", "B", "C", "D", "E", "F"][..count].join(" ") + &" &".repeat(count - 1);
+            let table = truth_table(&formula).unwrap();
+            assert_eq!(table.len(), 1 << count);
+            assert_eq!(table.minterms(), vec![(1 << count) - 1]);
+        }
+        assert!(sat("ABCDE&&&&"));
+        assert!(sat("A"));
+    }
+
+    #[test]
+    fn test_truth_table_limit() {
+        let formula = Evaluator::new("ABC&&");
+        assert_eq!(
+            TruthTable::with_limit(&formula, 2).unwrap_err(),
+            FormulaError::TooManyVariables { count: 3, limit: 2 }
+        );
+        assert_eq!(TruthTable::with_limit(&formula, 3).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_packed_outputs() {
+        let table = truth_table("A B C D E F G & & & & & &").unwrap();
+        assert_eq!(table.len(), 128);
+        assert_eq!(table.output_words(), [0, 1 << 63]);
+        assert_eq!(table.minterms(), vec![127]);
+        assert_eq!(truth_table("1").unwrap().output_words(), [1]);
+    }
+
+    #[test]
+    fn test_tables_equal() {
+        assert_eq!(tables_equal("AB>", "A!B|"), Ok(true));
+        assert_eq!(tables_equal("AB&!", "A!B!|"), Ok(true));
+        assert_eq!(tables_equal("AB|", "BA|C&"), Ok(false));
+        assert_eq!(tables_equal("AA!|", "B B !|"), Ok(true));
+        assert!(tables_equal("A&", "A").is_err());
+    }
+
+    #[test]
+    fn test_sat_many_variables() {
+        let names = (0..300).map(|i| format!("x{i}")).collect::<Vec<String>>();
+        let chain = names.join(" ") + &" &".repeat(names.len() - 1);
+        assert!(sat(&chain));
+        assert!(!sat(&format!("{chain} x150 ! &")));
+        let model = satisfying_assignment(&format!("{chain} x0 y > &")).unwrap().unwrap();
+        assert_eq!(model.len(), 301);
+        assert!(Evaluator::new(&format!("{chain} x0 y > &")).eval(&model).unwrap());
+    }
+
+    #[test]
+    fn test_sat_xor_chain() {
+        let names = (0..100).map(|i| format!("x{i}")).collect::<Vec<String>>();
+        let chain = names.join(" ") + &" ^".repeat(names.len() - 1);
+        assert!(sat(&chain));
+        assert!(!sat(&format!("{chain} {chain} ^")));
+    }
+
+    #[test]
+    fn test_sat_identifiers() {
+        assert!(sat("door_open x17 & door_open ! |"));
+        assert!(!sat("door_open x17 ! & x17 &"));
+    }
 }
\ No newline at end of file
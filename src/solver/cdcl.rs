@@ -0,0 +1,855 @@
+use std::fmt;
+use std::io;
+
+use super::cnf::{CnfFormula, Lit, SolveResult};
+use super::drat::{ProofFormat, ProofStep};
+
+/// Conflicts allowed before the first restart, scaled by the Luby sequence afterwards.
+const RESTART_BASE: u64 = 100;
+const VARIABLE_DECAY: f64 = 0.95;
+const CLAUSE_DECAY: f64 = 0.999;
+const RESCALE_LIMIT: f64 = 1e100;
+
+/// Counters gathered by a `Solver` over all its `solve` calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub conflicts: u64,
+    pub decisions: u64,
+    pub propagations: u64,
+    pub restarts: u64,
+    pub learnt_clauses: u64,
+    pub deleted_clauses: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Clause {
+    /// The first two literals are the watched ones. For a reason clause, the first is the implied literal.
+    lits: Vec<Lit>,
+    learnt: bool,
+    deleted: bool,
+    activity: f64,
+}
+
+/// Max-heap of variables ordered by activity, with positions so an activity bump can sift up.
+#[derive(Debug, Clone, Default)]
+struct VariableOrder {
+    heap: Vec<usize>,
+    positions: Vec<Option<usize>>,
+}
+
+impl VariableOrder {
+    fn contains(&self, variable: usize) -> bool {
+        self.positions[variable].is_some()
+    }
+
+    fn insert(&mut self, variable: usize, activity: &[f64]) {
+        if !self.contains(variable) {
+            self.positions[variable] = Some(self.heap.len());
+            self.heap.push(variable);
+            self.sift_up(self.heap.len() - 1, activity);
+        }
+    }
+
+    fn pop(&mut self, activity: &[f64]) -> Option<usize> {
+        let top = *self.heap.first()?;
+        let last = self.heap.pop().unwrap();
+        self.positions[top] = None;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.positions[last] = Some(0);
+            self.sift_down(0, activity);
+        }
+        Some(top)
+    }
+
+    fn sift_up(&mut self, mut index: usize, activity: &[f64]) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if activity[self.heap[parent]] >= activity[self.heap[index]] {
+                break;
+            }
+            self.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut index: usize, activity: &[f64]) {
+        loop {
+            let mut largest = index;
+            for child in [2 * index + 1, 2 * index + 2] {
+                if child < self.heap.len() && activity[self.heap[child]] > activity[self.heap[largest]] {
+                    largest = child;
+                }
+            }
+            if largest == index {
+                return;
+            }
+            self.swap(index, largest);
+            index = largest;
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = Some(a);
+        self.positions[self.heap[b]] = Some(b);
+    }
+}
+
+/// Clause database under the two-watched-literal scheme, shared by `Solver` and the DRAT
+/// checker: the first two literals of a clause are watched, and the clause is in the watch
+/// lists of both.
+pub(super) trait WatchedClauses {
+    fn value(&self, lit: Lit) -> Option<bool>;
+
+    fn lits_mut(&mut self, clause: usize) -> &mut [Lit];
+
+    /// Clauses watching `lit`, visited when it becomes false.
+    fn watchers_mut(&mut self, lit: Lit) -> &mut Vec<usize>;
+
+    /// Assigns `lit`, the first literal of `reason`, the others being false.
+    fn imply(&mut self, lit: Lit, reason: usize);
+
+    /// Visits the clauses watching `false_lit`, which has just become false. Each watches
+    /// another literal that is not false if it has one, and otherwise implies its other
+    /// watched literal. Returns the first clause found falsified.
+    fn visit_watchers(&mut self, false_lit: Lit) -> Option<usize> {
+        let mut watchers = std::mem::take(self.watchers_mut(false_lit));
+        let mut kept = 0;
+        let mut conflict = None;
+        let mut i = 0;
+        while i < watchers.len() {
+            let index = watchers[i];
+            i += 1;
+            let lits = self.lits_mut(index);
+            if lits[0] == false_lit {
+                lits.swap(0, 1);
+            }
+            let (first, len) = (lits[0], lits.len());
+            if self.value(first) == Some(true) {
+                watchers[kept] = index;
+                kept += 1;
+                continue;
+            }
+            if let Some(k) = (2..len).find(|&k| {
+                let lit = self.lits_mut(index)[k];
+                self.value(lit) != Some(false)
+            }) {
+                let lits = self.lits_mut(index);
+                lits.swap(1, k);
+                let watched = lits[1];
+                self.watchers_mut(watched).push(index);
+                continue;
+            }
+            watchers[kept] = index;
+            kept += 1;
+            if self.value(first) == Some(false) {
+                conflict = Some(index);
+                while i < watchers.len() {
+                    watchers[kept] = watchers[i];
+                    kept += 1;
+                    i += 1;
+                }
+            } else {
+                self.imply(first, index);
+            }
+        }
+        watchers.truncate(kept);
+        *self.watchers_mut(false_lit) = watchers;
+        conflict
+    }
+}
+
+/// Destination of the DRAT steps of a `Solver`. The first write error stops the logging
+/// and is kept for `finish_proof`.
+struct Proof<'a> {
+    out: Box<dyn io::Write + 'a>,
+    format: ProofFormat,
+    error: Option<io::Error>,
+}
+
+impl Proof<'_> {
+    fn log(&mut self, step: ProofStep) {
+        if self.error.is_none() {
+            if let Err(e) = self.format.write_step(&mut self.out, &step) {
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Proof<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proof").field("format", &self.format).finish_non_exhaustive()
+    }
+}
+
+/// Conflict-driven clause-learning solver: two watched literals per clause, first-UIP
+/// learning, VSIDS branching with phase saving, Luby restarts and activity-based deletion
+/// of learnt clauses. `'a` is the lifetime of the proof sink given to `log_proof`.
+#[derive(Debug)]
+pub struct Solver<'a> {
+    clauses: Vec<Clause>,
+    /// Clauses watching each literal, indexed by `Lit::code`, visited when that literal becomes false.
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+    /// Trail length at the start of each decision level.
+    trail_limits: Vec<usize>,
+    propagated: usize,
+    activity: Vec<f64>,
+    variable_increment: f64,
+    clause_increment: f64,
+    order: VariableOrder,
+    phases: Vec<bool>,
+    seen: Vec<bool>,
+    /// Learnt clauses currently attached.
+    learnt_count: usize,
+    max_learnts: f64,
+    /// Literals decided first, one per decision level, by `solve_with_assumptions`.
+    assumptions: Vec<Lit>,
+    /// Assumptions responsible for the last unsatisfiable answer.
+    failed: Vec<Lit>,
+    /// Cleared once the clauses are known to be unsatisfiable at level 0.
+    ok: bool,
+    stats: Stats,
+    proof: Option<Proof<'a>>,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(formula: &CnfFormula) -> Self {
+        let mut solver = Solver {
+            clauses: vec![],
+            watches: vec![],
+            values: vec![],
+            levels: vec![],
+            reasons: vec![],
+            trail: vec![],
+            trail_limits: vec![],
+            propagated: 0,
+            activity: vec![],
+            variable_increment: 1.0,
+            clause_increment: 1.0,
+            order: VariableOrder::default(),
+            phases: vec![],
+            seen: vec![],
+            learnt_count: 0,
+            max_learnts: formula.clauses().len() as f64 / 3.0 + 10.0,
+            assumptions: vec![],
+            failed: vec![],
+            ok: true,
+            stats: Stats::default(),
+            proof: None,
+        };
+        solver.reserve_variables(formula.variable_count());
+        for clause in formula.clauses() {
+            solver.add_clause(clause);
+        }
+        solver
+    }
+
+    /// Starts logging a DRAT proof to `out`: every learnt clause is added, every deleted one
+    /// removed, and the empty clause closes the proof once the clauses are refuted. Clauses
+    /// given to the solver are the formula the proof refers to; an `Unsat` that only holds
+    /// under assumptions is not refuted in the proof.
+    pub fn log_proof(&mut self, out: Box<dyn io::Write + 'a>, format: ProofFormat) {
+        self.proof = Some(Proof { out, format, error: None });
+        if !self.ok {
+            self.log(ProofStep::Add(vec![]));
+        }
+    }
+
+    /// Stops logging, flushing the proof and reporting the first error met while writing it.
+    pub fn finish_proof(&mut self) -> io::Result<()> {
+        match self.proof.take() {
+            None => Ok(()),
+            Some(mut proof) => match proof.error.take() {
+                Some(e) => Err(e),
+                None => proof.out.flush(),
+            },
+        }
+    }
+
+    fn log(&mut self, step: ProofStep) {
+        if let Some(proof) = &mut self.proof {
+            proof.log(step);
+        }
+    }
+
+    /// Records that the clauses are unsatisfiable.
+    fn refute(&mut self) {
+        self.ok = false;
+        self.log(ProofStep::Add(vec![]));
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Allocates a variable that no clause mentions yet.
+    pub fn new_variable(&mut self) -> usize {
+        let variable = self.values.len();
+        self.reserve_variables(variable + 1);
+        variable
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Subset of the assumptions of the last call that is already unsatisfiable with the
+    /// clauses, empty when the clauses alone are.
+    pub fn failed_assumptions(&self) -> &[Lit] {
+        &self.failed
+    }
+
+    fn reserve_variables(&mut self, count: usize) {
+        while self.values.len() < count {
+            let variable = self.values.len();
+            self.values.push(None);
+            self.levels.push(0);
+            self.reasons.push(None);
+            self.activity.push(0.0);
+            self.phases.push(false);
+            self.seen.push(false);
+            self.watches.extend([vec![], vec![]]);
+            self.order.positions.push(None);
+            self.order.insert(variable, &self.activity);
+        }
+    }
+
+    /// Adds a clause, for instance one blocking a model returned by `solve`. Learnt clauses
+    /// stay valid since they follow from the previous clauses.
+    pub fn add_clause(&mut self, clause: &[Lit]) {
+        if !self.ok {
+            return;
+        }
+        if let Some(max) = clause.iter().map(|lit| lit.variable() + 1).max() {
+            self.reserve_variables(max);
+        }
+        let mut lits = clause.to_vec();
+        lits.sort();
+        lits.dedup();
+        if lits.windows(2).any(|pair| pair[1] == !pair[0]) || lits.iter().any(|&lit| self.value(lit) == Some(true)) {
+            return;
+        }
+        lits.retain(|&lit| self.value(lit).is_none());
+        match lits.len() {
+            0 => self.refute(),
+            1 => {
+                self.enqueue(lits[0], None);
+                if self.propagate().is_some() {
+                    self.refute();
+                }
+            }
+            _ => {
+                self.attach(lits, false);
+            }
+        }
+    }
+
+    fn attach(&mut self, lits: Vec<Lit>, learnt: bool) -> usize {
+        let index = self.clauses.len();
+        self.watches[lits[0].code()].push(index);
+        self.watches[lits[1].code()].push(index);
+        self.clauses.push(Clause { lits, learnt, deleted: false, activity: 0.0 });
+        index
+    }
+
+    fn decision_level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        let variable = lit.variable();
+        self.values[variable] = Some(!lit.is_negated());
+        self.levels[variable] = self.decision_level();
+        self.reasons[variable] = reason;
+        self.trail.push(lit);
+    }
+
+    /// Propagates the trail through the watch lists, returning a falsified clause on conflict.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_lit = !self.trail[self.propagated];
+            self.propagated += 1;
+            self.stats.propagations += 1;
+            if let Some(conflict) = self.visit_watchers(false_lit) {
+                self.propagated = self.trail.len();
+                return Some(conflict);
+            }
+        }
+        None
+    }
+
+    /// First-UIP analysis of `conflict`: returns the learnt clause with its asserting literal
+    /// first and a literal of the backjump level second, along with that level.
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, usize) {
+        let mut learnt = vec![];
+        let mut pending = 0;
+        let mut index = self.trail.len();
+        let mut clause = conflict;
+        let mut implied: Option<Lit> = None;
+        loop {
+            self.bump_clause(clause);
+            let skip = implied.is_some() as usize;
+            for j in skip..self.clauses[clause].lits.len() {
+                let lit = self.clauses[clause].lits[j];
+                let variable = lit.variable();
+                if !self.seen[variable] && self.levels[variable] > 0 {
+                    self.seen[variable] = true;
+                    self.bump_variable(variable);
+                    if self.levels[variable] == self.decision_level() {
+                        pending += 1;
+                    } else {
+                        learnt.push(lit);
+                    }
+                }
+            }
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index].variable()] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            self.seen[lit.variable()] = false;
+            implied = Some(lit);
+            pending -= 1;
+            if pending == 0 {
+                break;
+            }
+            clause = self.reasons[lit.variable()].expect("only the decision of a level has no reason");
+        }
+        learnt.insert(0, !implied.unwrap());
+
+        // Drop literals implied by the rest of the clause through their own reason.
+        let minimised = learnt
+            .iter()
+            .enumerate()
+            .filter(|&(j, lit)| {
+                j == 0
+                    || match self.reasons[lit.variable()] {
+                        None => true,
+                        Some(reason) => self.clauses[reason].lits[1..]
+                            .iter()
+                            .any(|other| !self.seen[other.variable()] && self.levels[other.variable()] > 0),
+                    }
+            })
+            .map(|(_, &lit)| lit)
+            .collect::<Vec<Lit>>();
+        for lit in &learnt {
+            self.seen[lit.variable()] = false;
+        }
+        let mut learnt = minimised;
+
+        let mut level = 0;
+        if learnt.len() > 1 {
+            let second = (1..learnt.len()).max_by_key(|&j| self.levels[learnt[j].variable()]).unwrap();
+            learnt.swap(1, second);
+            level = self.levels[learnt[1].variable()];
+        }
+        (learnt, level)
+    }
+
+    fn bump_variable(&mut self, variable: usize) {
+        self.activity[variable] += self.variable_increment;
+        if self.activity[variable] > RESCALE_LIMIT {
+            for activity in &mut self.activity {
+                *activity /= RESCALE_LIMIT;
+            }
+            self.variable_increment /= RESCALE_LIMIT;
+        }
+        if let Some(position) = self.order.positions[variable] {
+            self.order.sift_up(position, &self.activity);
+        }
+    }
+
+    fn bump_clause(&mut self, index: usize) {
+        if !self.clauses[index].learnt {
+            return;
+        }
+        self.clauses[index].activity += self.clause_increment;
+        if self.clauses[index].activity > RESCALE_LIMIT {
+            for clause in self.clauses.iter_mut().filter(|clause| clause.learnt) {
+                clause.activity /= RESCALE_LIMIT;
+            }
+            self.clause_increment /= RESCALE_LIMIT;
+        }
+    }
+
+    /// Undoes every assignment above `level`, saving the phases of the unassigned variables.
+    fn cancel_until(&mut self, level: usize) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let start = self.trail_limits[level];
+        for lit in self.trail.drain(start..) {
+            let variable = lit.variable();
+            self.phases[variable] = !lit.is_negated();
+            self.values[variable] = None;
+            self.reasons[variable] = None;
+            self.order.insert(variable, &self.activity);
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = self.trail.len();
+    }
+
+    fn pick_branch(&mut self) -> Option<Lit> {
+        while let Some(variable) = self.order.pop(&self.activity) {
+            if self.values[variable].is_none() {
+                return Some(Lit::new(variable, !self.phases[variable]));
+            }
+        }
+        None
+    }
+
+    fn is_locked(&self, index: usize) -> bool {
+        let first = self.clauses[index].lits[0];
+        self.reasons[first.variable()] == Some(index) && self.value(first) == Some(true)
+    }
+
+    /// Deletes the less active half of the learnt clauses, keeping binary clauses and reasons.
+    fn reduce_learnts(&mut self) {
+        let mut candidates = (0..self.clauses.len())
+            .filter(|&index| {
+                let clause = &self.clauses[index];
+                clause.learnt && !clause.deleted && clause.lits.len() > 2 && !self.is_locked(index)
+            })
+            .collect::<Vec<usize>>();
+        candidates.sort_by(|&a, &b| self.clauses[a].activity.total_cmp(&self.clauses[b].activity));
+        for &index in &candidates[..candidates.len() / 2] {
+            self.clauses[index].deleted = true;
+            let lits = std::mem::take(&mut self.clauses[index].lits);
+            self.log(ProofStep::Delete(lits));
+            self.stats.deleted_clauses += 1;
+        }
+        self.learnt_count -= candidates.len() / 2;
+        for watchers in &mut self.watches {
+            watchers.retain(|&index| !self.clauses[index].deleted);
+        }
+    }
+
+    /// Runs until a model, a refutation, or `budget` conflicts, which returns `None` to restart.
+    fn search(&mut self, budget: u64) -> Option<bool> {
+        let mut conflicts = 0;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                self.stats.conflicts += 1;
+                conflicts += 1;
+                if self.decision_level() == 0 {
+                    self.refute();
+                    return Some(false);
+                }
+                let (learnt, level) = self.analyze(conflict);
+                self.log(ProofStep::Add(learnt.clone()));
+                self.cancel_until(level);
+                self.stats.learnt_clauses += 1;
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                } else {
+                    let asserting = learnt[0];
+                    let index = self.attach(learnt, true);
+                    self.learnt_count += 1;
+                    self.bump_clause(index);
+                    self.enqueue(asserting, Some(index));
+                }
+                self.variable_increment /= VARIABLE_DECAY;
+                self.clause_increment /= CLAUSE_DECAY;
+            } else {
+                if conflicts >= budget {
+                    self.cancel_until(0);
+                    return None;
+                }
+                if self.learnt_count as f64 - self.trail.len() as f64 >= self.max_learnts {
+                    self.reduce_learnts();
+                }
+                let mut next = None;
+                while self.decision_level() < self.assumptions.len() {
+                    let assumption = self.assumptions[self.decision_level()];
+                    match self.value(assumption) {
+                        // Keep one level per assumption so their levels line up.
+                        Some(true) => self.trail_limits.push(self.trail.len()),
+                        Some(false) => {
+                            self.failed = self.analyze_final(assumption);
+                            return Some(false);
+                        }
+                        None => {
+                            next = Some(assumption);
+                            break;
+                        }
+                    }
+                }
+                let lit = match next {
+                    Some(assumption) => assumption,
+                    None => match self.pick_branch() {
+                        None => return Some(true),
+                        Some(lit) => {
+                            self.stats.decisions += 1;
+                            lit
+                        }
+                    },
+                };
+                self.trail_limits.push(self.trail.len());
+                self.enqueue(lit, None);
+            }
+        }
+    }
+
+    /// Assumptions that, with the clauses, imply the negation of `assumption`, which is false.
+    fn analyze_final(&mut self, assumption: Lit) -> Vec<Lit> {
+        let mut failed = vec![assumption];
+        if self.levels[assumption.variable()] == 0 {
+            return failed;
+        }
+        self.seen[assumption.variable()] = true;
+        for index in (self.trail_limits[0]..self.trail.len()).rev() {
+            let lit = self.trail[index];
+            if !self.seen[lit.variable()] {
+                continue;
+            }
+            match self.reasons[lit.variable()] {
+                // Every decision below the assumption levels is an assumption.
+                None => failed.push(lit),
+                Some(reason) => {
+                    for j in 1..self.clauses[reason].lits.len() {
+                        let other = self.clauses[reason].lits[j].variable();
+                        if self.levels[other] > 0 {
+                            self.seen[other] = true;
+                        }
+                    }
+                }
+            }
+            self.seen[lit.variable()] = false;
+        }
+        self.seen[assumption.variable()] = false;
+        failed
+    }
+
+    /// Decides the clauses, returning a model indexed by variable when they are satisfiable.
+    pub fn solve(&mut self) -> SolveResult {
+        self.solve_with_assumptions(&[])
+    }
+
+    /// Decides the clauses with every literal of `assumptions` forced true for this call only.
+    /// On `Unsat`, `failed_assumptions` tells which of them were needed.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> SolveResult {
+        self.failed.clear();
+        if !self.ok {
+            return SolveResult::Unsat;
+        }
+        if let Some(max) = assumptions.iter().map(|lit| lit.variable() + 1).max() {
+            self.reserve_variables(max);
+        }
+        self.assumptions = assumptions.to_vec();
+        let mut restarts = 0;
+        loop {
+            match self.search(luby(restarts) * RESTART_BASE) {
+                Some(true) => {
+                    let model = self.values.iter().map(|value| value.unwrap_or(false)).collect();
+                    self.cancel_until(0);
+                    return SolveResult::Sat(model);
+                }
+                Some(false) => {
+                    self.cancel_until(0);
+                    return SolveResult::Unsat;
+                }
+                None => {
+                    restarts += 1;
+                    self.stats.restarts += 1;
+                    self.max_learnts *= 1.1;
+                }
+            }
+        }
+    }
+}
+
+impl WatchedClauses for Solver<'_> {
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.variable()].map(|value| lit.apply(value))
+    }
+
+    fn lits_mut(&mut self, clause: usize) -> &mut [Lit] {
+        &mut self.clauses[clause].lits
+    }
+
+    fn watchers_mut(&mut self, lit: Lit) -> &mut Vec<usize> {
+        &mut self.watches[lit.code()]
+    }
+
+    fn imply(&mut self, lit: Lit, reason: usize) {
+        self.enqueue(lit, Some(reason));
+    }
+}
+
+/// Decides `formula` with a fresh `Solver`.
+pub fn solve(formula: &CnfFormula) -> SolveResult {
+    Solver::new(formula).solve()
+}
+
+/// Term `index` of the Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, …
+fn luby(mut index: u64) -> u64 {
+    let mut size = 1;
+    let mut exponent = 0;
+    while size < index + 1 {
+        exponent += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != index {
+        size = (size - 1) / 2;
+        exponent -= 1;
+        index %= size;
+    }
+    1 << exponent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dpll;
+
+    /// Random 3-SAT instance from a xorshift generator, so the tests stay deterministic.
+    fn random_formula(seed: u64, variables: usize, clauses: usize) -> CnfFormula {
+        let mut state = seed;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut formula = CnfFormula::new(variables);
+        for _ in 0..clauses {
+            let clause = (0..3)
+                .map(|_| Lit::new(next() as usize % variables, next() & 1 == 1))
+                .collect();
+            formula.add_clause(clause);
+        }
+        formula
+    }
+
+    fn pigeonhole(holes: usize) -> CnfFormula {
+        let pigeons = holes + 1;
+        let mut formula = CnfFormula::new(pigeons * holes);
+        for p in 0..pigeons {
+            formula.add_clause((0..holes).map(|h| Lit::positive(holes * p + h)).collect());
+        }
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    formula.add_clause(vec![Lit::negative(holes * p + h), Lit::negative(holes * q + h)]);
+                }
+            }
+        }
+        formula
+    }
+
+    #[test]
+    fn test_luby() {
+        assert_eq!((0..15).map(luby).collect::<Vec<u64>>(), [1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_agrees_with_dpll() {
+        for seed in 1..60 {
+            let formula = random_formula(seed * 0x9E37_79B9, 20, 88);
+            let result = solve(&formula);
+            assert_eq!(result.is_sat(), dpll::solve(&formula).is_sat(), "seed {seed}");
+            if let SolveResult::Sat(model) = result {
+                assert!(formula.is_satisfied_by(&model), "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_pigeonhole_and_stats() {
+        let mut solver = Solver::new(&pigeonhole(6));
+        assert_eq!(solver.solve(), SolveResult::Unsat);
+        let stats = solver.stats();
+        assert!(stats.conflicts > 0 && stats.decisions > 0 && stats.propagations > 0);
+        assert_eq!(stats.learnt_clauses, stats.conflicts - 1);
+        assert_eq!(solver.solve(), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn test_assumptions_and_added_clauses() {
+        let mut formula = CnfFormula::new(3);
+        formula.add_clause(vec![Lit::positive(0), Lit::positive(1)]);
+        formula.add_clause(vec![Lit::negative(1), Lit::positive(2)]);
+        let mut solver = Solver::new(&formula);
+        assert_eq!(solver.solve_with_assumptions(&[Lit::negative(0), Lit::negative(2)]), SolveResult::Unsat);
+        let mut failed = solver.failed_assumptions().to_vec();
+        failed.sort();
+        assert_eq!(failed, [Lit::negative(0), Lit::negative(2)]);
+
+        // The assumptions only hold for their call.
+        let model = solver.solve_with_assumptions(&[Lit::negative(0)]).model().map(<[bool]>::to_vec).unwrap();
+        assert_eq!(model, [false, true, true]);
+        assert!(solver.failed_assumptions().is_empty());
+        let extra = solver.new_variable();
+        solver.add_clause(&[Lit::negative(2), Lit::positive(extra)]);
+        assert_eq!(solver.solve_with_assumptions(&[Lit::negative(extra), Lit::positive(1)]), SolveResult::Unsat);
+        assert_eq!(solver.failed_assumptions().len(), 2);
+        solver.add_clause(&[Lit::negative(extra)]);
+        assert!(solver.solve_with_assumptions(&[Lit::positive(0)]).is_sat());
+        solver.add_clause(&[Lit::negative(0)]);
+        assert_eq!(solver.solve_with_assumptions(&[Lit::positive(extra)]), SolveResult::Unsat);
+        assert!(solver.failed_assumptions().is_empty());
+    }
+
+    #[test]
+    fn test_drat_proofs_check() {
+        use crate::solver::drat::{check_drat, solve_with_proof};
+
+        for format in [ProofFormat::Text, ProofFormat::Binary] {
+            for formula in [pigeonhole(5), random_formula(11, 30, 200)] {
+                let mut proof = vec![];
+                assert_eq!(solve_with_proof(&formula, &mut proof, format).unwrap(), SolveResult::Unsat);
+                assert_eq!(check_drat(&formula, &proof, format), Ok(()));
+            }
+        }
+        let mut formula = pigeonhole(3);
+        formula.add_clause(vec![]);
+        let mut proof = vec![];
+        solve_with_proof(&formula, &mut proof, ProofFormat::Text).unwrap();
+        assert_eq!(proof, b"0\n");
+
+        // Steps reach the sink one by one rather than in a single final write.
+        struct Steps(usize);
+        impl io::Write for Steps {
+            fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+                self.0 += 1;
+                Ok(bytes.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut steps = Steps(0);
+        solve_with_proof(&pigeonhole(5), &mut steps, ProofFormat::Binary).unwrap();
+        assert!(steps.0 > 100);
+    }
+
+    #[test]
+    fn test_trivial_formulas() {
+        assert!(solve(&CnfFormula::new(3)).is_sat());
+        let mut formula = CnfFormula::new(2);
+        formula.add_clause(vec![Lit::positive(0)]);
+        formula.add_clause(vec![Lit::negative(0), Lit::positive(1)]);
+        assert_eq!(solve(&formula), SolveResult::Sat(vec![true, true]));
+        formula.add_clause(vec![Lit::negative(1)]);
+        assert_eq!(solve(&formula), SolveResult::Unsat);
+        formula.add_clause(vec![]);
+        assert_eq!(solve(&formula), SolveResult::Unsat);
+    }
+
+    #[test]
+    fn test_large_satisfiable_instance() {
+        let formula = random_formula(7, 250, 900);
+        let mut solver = Solver::new(&formula);
+        let model = solver.solve().model().map(<[bool]>::to_vec).unwrap();
+        assert!(formula.is_satisfied_by(&model));
+    }
+}
@@ -0,0 +1,277 @@
+use std::fmt;
+use std::ops::Not;
+
+use crate::boolean_evaluator::{boolean_evaluator::Evaluator, variables::VariableTable};
+
+/// Variable index or its negation, packed as `2 * variable + negated`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Lit(u32);
+
+impl Lit {
+    /// Largest variable index a literal can hold.
+    pub const MAX_VARIABLE: usize = (u32::MAX >> 1) as usize;
+
+    /// Panics when `variable` exceeds `MAX_VARIABLE`, rather than aliasing another variable.
+    pub fn new(variable: usize, negated: bool) -> Self {
+        let variable = u32::try_from(variable).ok().filter(|&variable| variable as usize <= Self::MAX_VARIABLE);
+        Lit(variable.expect("variable index exceeds Lit::MAX_VARIABLE") << 1 | negated as u32)
+    }
+
+    pub fn positive(variable: usize) -> Self {
+        Self::new(variable, false)
+    }
+
+    pub fn negative(variable: usize) -> Self {
+        Self::new(variable, true)
+    }
+
+    pub fn variable(self) -> usize {
+        (self.0 >> 1) as usize
+    }
+
+    pub fn is_negated(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    /// Dense index `2 * variable + negated`, for tables indexed by literal.
+    pub fn code(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Literal written `value` in DIMACS, where variable `i` is numbered `i + 1`. `value` must not be 0.
+    pub fn from_dimacs(value: i64) -> Self {
+        assert_ne!(value, 0, "0 terminates a DIMACS clause and is not a literal");
+        Self::new(value.unsigned_abs() as usize - 1, value < 0)
+    }
+
+    pub fn to_dimacs(self) -> i64 {
+        let number = self.variable() as i64 + 1;
+        if self.is_negated() { -number } else { number }
+    }
+
+    /// Value of the literal once its variable is set to `value`.
+    pub fn apply(self, value: bool) -> bool {
+        value != self.is_negated()
+    }
+}
+
+impl Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        Lit(self.0 ^ 1)
+    }
+}
+
+impl fmt::Display for Lit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", if self.is_negated() { "!" } else { "" }, self.variable())
+    }
+}
+
+/// Conjunction of clauses over variables `0..variable_count()`, each clause a disjunction of literals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CnfFormula {
+    variable_count: usize,
+    clauses: Vec<Vec<Lit>>,
+}
+
+impl CnfFormula {
+    pub fn new(variable_count: usize) -> Self {
+        CnfFormula { variable_count, clauses: vec![] }
+    }
+
+    /// Clause database of `evaluator`, whose variable `i` is `variables.name(i)`. Clauses are
+    /// expanded by distribution, so their number can grow exponentially with nested `^`, `=` and `|`.
+    pub fn from_evaluator(evaluator: &Evaluator) -> (Self, VariableTable) {
+        let variables = evaluator.variable_table();
+        let mut formula = CnfFormula::new(variables.len());
+        formula.clauses = expand(evaluator, false, &variables);
+        (formula, variables)
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.variable_count
+    }
+
+    /// Allocates a fresh variable and returns its index.
+    pub fn new_variable(&mut self) -> usize {
+        self.variable_count += 1;
+        self.variable_count - 1
+    }
+
+    /// Adds a clause, growing the variable range to cover its literals.
+    pub fn add_clause(&mut self, clause: Vec<Lit>) {
+        if let Some(max) = clause.iter().map(|lit| lit.variable() + 1).max() {
+            self.variable_count = self.variable_count.max(max);
+        }
+        self.clauses.push(clause);
+    }
+
+    pub fn clauses(&self) -> &[Vec<Lit>] {
+        &self.clauses
+    }
+
+    /// Conjunction of the clauses as a tree, naming variable `i` after `variables.name(i)`.
+    pub fn to_evaluator(&self, variables: &VariableTable) -> Evaluator {
+        let literal = |lit: Lit| {
+            let variable = Evaluator::Variable(variables.name(lit.variable()).to_string());
+            if lit.is_negated() { Evaluator::Not(Box::new(variable)) } else { variable }
+        };
+        let clause = |lits: &Vec<Lit>| {
+            lits.iter()
+                .map(|&lit| literal(lit))
+                .reduce(|left, right| Evaluator::Or(Box::new(left), Box::new(right)))
+                .unwrap_or(Evaluator::False)
+        };
+        self.clauses
+            .iter()
+            .map(clause)
+            .reduce(|left, right| Evaluator::And(Box::new(left), Box::new(right)))
+            .unwrap_or(Evaluator::True)
+    }
+
+    /// Whether `model`, indexed by variable, satisfies every clause.
+    pub fn is_satisfied_by(&self, model: &[bool]) -> bool {
+        self.clauses
+            .iter()
+            .all(|clause| clause.iter().any(|lit| lit.apply(model[lit.variable()])))
+    }
+}
+
+/// Clauses of `evaluator`, or of its negation when `negated`. Constants need no folding:
+/// `True` is the empty conjunction and `False` the conjunction of the empty clause.
+fn expand(evaluator: &Evaluator, negated: bool, variables: &VariableTable) -> Vec<Vec<Lit>> {
+    let clauses = |operand: &Evaluator, negated| expand(operand, negated, variables);
+    match evaluator {
+        Evaluator::True | Evaluator::False => {
+            if matches!(evaluator, Evaluator::True) == negated { vec![vec![]] } else { vec![] }
+        }
+        Evaluator::Variable(name) => vec![vec![Lit::new(variables.index(name).unwrap(), negated)]],
+        Evaluator::Not(operand) => clauses(operand, !negated),
+        Evaluator::And(a, b) | Evaluator::Nand(a, b) => {
+            if matches!(evaluator, Evaluator::And(..)) != negated {
+                conjunction(clauses(a, false), clauses(b, false))
+            } else {
+                disjunction(clauses(a, true), clauses(b, true))
+            }
+        }
+        Evaluator::Or(a, b) | Evaluator::Nor(a, b) => {
+            if matches!(evaluator, Evaluator::Or(..)) != negated {
+                disjunction(clauses(a, false), clauses(b, false))
+            } else {
+                conjunction(clauses(a, true), clauses(b, true))
+            }
+        }
+        Evaluator::Xor(a, b) | Evaluator::Equivalence(a, b) | Evaluator::Xnor(a, b) => {
+            // Differing operands: (a | b) & (!a | !b). Equal operands: (!a | b) & (a | !b).
+            let differ = matches!(evaluator, Evaluator::Xor(..)) != negated;
+            conjunction(
+                disjunction(clauses(a, !differ), clauses(b, false)),
+                disjunction(clauses(a, differ), clauses(b, true)),
+            )
+        }
+        Evaluator::Conditional(a, b) | Evaluator::Converse(b, a) => {
+            if negated {
+                conjunction(clauses(a, false), clauses(b, true))
+            } else {
+                disjunction(clauses(a, true), clauses(b, false))
+            }
+        }
+        Evaluator::IfThenElse(condition, then, otherwise) => conjunction(
+            disjunction(clauses(condition, true), clauses(then, negated)),
+            disjunction(clauses(condition, false), clauses(otherwise, negated)),
+        ),
+    }
+}
+
+fn conjunction(mut left: Vec<Vec<Lit>>, right: Vec<Vec<Lit>>) -> Vec<Vec<Lit>> {
+    left.extend(right);
+    left
+}
+
+/// Distributes `left | right` into clauses, dropping the ones holding a literal and its negation.
+fn disjunction(left: Vec<Vec<Lit>>, right: Vec<Vec<Lit>>) -> Vec<Vec<Lit>> {
+    let mut product = vec![];
+    for left in &left {
+        for right in &right {
+            let mut clause = left.iter().chain(right).copied().collect::<Vec<Lit>>();
+            clause.sort();
+            clause.dedup();
+            if !clause.windows(2).any(|pair| pair[1] == !pair[0]) {
+                product.push(clause);
+            }
+        }
+    }
+    product
+}
+
+/// Outcome of a satisfiability check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveResult {
+    /// A model giving a value to every variable, indexed by variable.
+    Sat(Vec<bool>),
+    Unsat,
+}
+
+impl SolveResult {
+    pub fn is_sat(&self) -> bool {
+        matches!(self, SolveResult::Sat(_))
+    }
+
+    pub fn model(&self) -> Option<&[bool]> {
+        match self {
+            SolveResult::Sat(model) => Some(model),
+            SolveResult::Unsat => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boolean_evaluator::assignment::Bitmask;
+
+    #[test]
+    fn test_lit() {
+        let lit = Lit::negative(3);
+        assert_eq!(lit.variable(), 3);
+        assert!(lit.is_negated());
+        assert_eq!(!lit, Lit::positive(3));
+        assert_eq!(lit.code(), 7);
+        assert!(lit.apply(false));
+        assert_eq!(lit.to_string(), "!x3");
+        assert_eq!(Lit::from_dimacs(-4), lit);
+        assert_eq!(Lit::positive(0).to_dimacs(), 1);
+        assert_eq!(Lit::negative(Lit::MAX_VARIABLE).variable(), Lit::MAX_VARIABLE);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds Lit::MAX_VARIABLE")]
+    fn test_lit_out_of_range() {
+        Lit::positive(Lit::MAX_VARIABLE + 1);
+    }
+
+    #[test]
+    fn test_from_evaluator() {
+        let (formula, variables) = CnfFormula::from_evaluator(&Evaluator::new("AB>C|"));
+        assert_eq!(variables.names(), ["A", "B", "C"]);
+        assert_eq!(formula.clauses(), [vec![Lit::negative(0), Lit::positive(1), Lit::positive(2)]]);
+        assert_eq!(CnfFormula::from_evaluator(&Evaluator::new("AA!|")).0.clauses().len(), 0);
+        assert!(CnfFormula::from_evaluator(&Evaluator::new("A0&")).0.clauses().contains(&vec![]));
+        assert!(CnfFormula::from_evaluator(&Evaluator::new("A1|")).0.clauses().is_empty());
+    }
+
+    #[test]
+    fn test_from_evaluator_preserves_models() {
+        for formula in ["AB^C=", "AB/C#!", "ABC?!", "AB%C<", "AB>!C|", "A0^B1=&"] {
+            let evaluator = Evaluator::new(formula);
+            let (clauses, variables) = CnfFormula::from_evaluator(&evaluator);
+            for row in 0..1u64 << variables.len() {
+                let model = (0..variables.len()).map(|i| row & (1 << i) != 0).collect::<Vec<bool>>();
+                let expected = evaluator.eval(&Bitmask::new(&variables, row)).unwrap();
+                assert_eq!(clauses.is_satisfied_by(&model), expected, "{formula} row {row}");
+            }
+        }
+    }
+}
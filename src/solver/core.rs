@@ -0,0 +1,115 @@
+use crate::boolean_evaluator::boolean_evaluator::Evaluator;
+
+use super::cdcl::Solver;
+use super::cnf::{CnfFormula, Lit, SolveResult};
+use super::tseitin::Encoding;
+
+/// Minimal unsatisfiable subset of named constraints, as their names in input order, or `None`
+/// when the conjunction of all of them is satisfiable. Dropping any constraint of the core
+/// makes the rest satisfiable.
+pub fn unsat_core<N: AsRef<str>>(constraints: &[(N, Evaluator)]) -> Option<Vec<String>> {
+    let evaluators = constraints.iter().map(|(_, evaluator)| evaluator).collect::<Vec<&Evaluator>>();
+    let (encoding, gates) = Encoding::gates(&evaluators);
+    let core = minimal_core(Solver::new(encoding.formula()), &gates)?;
+    Some(core.into_iter().map(|index| constraints[index].0.as_ref().to_string()).collect())
+}
+
+/// Minimal unsatisfiable subset of the clauses of `formula`, as increasing clause indices,
+/// or `None` when `formula` is satisfiable.
+pub fn unsat_core_cnf(formula: &CnfFormula) -> Option<Vec<usize>> {
+    // Clause `i` is extended with `!s` for a fresh selector `s`, enabled by assuming `s`.
+    let first = formula.variable_count();
+    let mut guarded = CnfFormula::new(first + formula.clauses().len());
+    let selectors = (0..formula.clauses().len()).map(|i| Lit::positive(first + i)).collect::<Vec<Lit>>();
+    for (clause, &selector) in formula.clauses().iter().zip(&selectors) {
+        guarded.add_clause(clause.iter().copied().chain([!selector]).collect());
+    }
+    minimal_core(Solver::new(&guarded), &selectors)
+}
+
+/// Indices of a minimal set of `selectors` whose assumption is unsatisfiable. The failed
+/// assumptions of the first refutation give a small core, which deletion then minimises:
+/// each selector is dropped in turn and kept out whenever the rest stays unsatisfiable.
+fn minimal_core(mut solver: Solver, selectors: &[Lit]) -> Option<Vec<usize>> {
+    if solver.solve_with_assumptions(selectors).is_sat() {
+        return None;
+    }
+    let mut core = failed_indices(&solver, selectors);
+    let mut necessary = vec![];
+    while let Some(&tried) = core.iter().find(|index| !necessary.contains(*index)) {
+        let assumptions = core
+            .iter()
+            .filter(|&&index| index != tried)
+            .map(|&index| selectors[index])
+            .collect::<Vec<Lit>>();
+        match solver.solve_with_assumptions(&assumptions) {
+            // The failed subset may be smaller still than the assumptions.
+            SolveResult::Unsat => core = failed_indices(&solver, selectors),
+            SolveResult::Sat(_) => necessary.push(tried),
+        }
+    }
+    Some(core)
+}
+
+fn failed_indices(solver: &Solver, selectors: &[Lit]) -> Vec<usize> {
+    let failed = solver.failed_assumptions();
+    (0..selectors.len()).filter(|&index| failed.contains(&selectors[index])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(constraints: &[(&str, &str)]) -> Vec<(String, Evaluator)> {
+        constraints
+            .iter()
+            .map(|(name, formula)| (name.to_string(), Evaluator::from_infix(formula).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unsat_core_of_named_constraints() {
+        let constraints = named(&[
+            ("heater", "heater -> power"),
+            ("alarm", "alarm | muted"),
+            ("budget", "!power"),
+            ("winter", "winter -> heater"),
+            ("cold", "winter"),
+            ("quiet", "!alarm"),
+        ]);
+        let core = unsat_core(&constraints).unwrap();
+        assert_eq!(core, ["heater", "budget", "winter", "cold"]);
+        assert_eq!(unsat_core(&constraints[..3]), None);
+    }
+
+    #[test]
+    fn test_unsat_core_is_minimal() {
+        let constraints = named(&[("a", "x"), ("b", "!x"), ("c", "y"), ("d", "!y"), ("e", "x & !y")]);
+        let core = unsat_core(&constraints).unwrap();
+        assert_eq!(core.len(), 2);
+        for skipped in 0..core.len() {
+            let rest = constraints
+                .iter()
+                .filter(|(name, _)| core.iter().enumerate().any(|(j, kept)| j != skipped && kept == name))
+                .cloned()
+                .collect::<Vec<_>>();
+            assert_eq!(unsat_core(&rest), None);
+        }
+    }
+
+    #[test]
+    fn test_unsat_core_cnf() {
+        let mut formula = CnfFormula::new(3);
+        for clause in [&[1, 2][..], &[-1, 2], &[3], &[1, -2], &[-1, -2], &[-3, 1]] {
+            formula.add_clause(clause.iter().map(|&value| Lit::from_dimacs(value)).collect());
+        }
+        let core = unsat_core_cnf(&formula).unwrap();
+        let mut subset = CnfFormula::new(3);
+        for &index in &core {
+            subset.add_clause(formula.clauses()[index].clone());
+        }
+        assert_eq!(crate::solver::cdcl::solve(&subset), SolveResult::Unsat);
+        assert!(core.len() <= 5);
+        assert_eq!(unsat_core_cnf(&CnfFormula::new(2)), None);
+    }
+}
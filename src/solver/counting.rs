@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::boolean_evaluator::{
+    boolean_evaluator::Evaluator,
+    compiled::CompiledEvaluator,
+};
+use crate::error::FormulaError;
+
+use super::cdcl;
+use super::cnf::{CnfFormula, Lit};
+use super::models::{projected_models, TABLE_VARIABLE_LIMIT};
+use super::tseitin::Encoding;
+
+/// Number of assignments of the variables of `evaluator` that satisfy it.
+///
+/// # Panics
+///
+/// Panics if the count does not fit in a `u128`, which takes at least 128 variables.
+pub fn count_models(evaluator: &Evaluator) -> u128 {
+    let variables = evaluator.variable_table();
+    if variables.len() <= TABLE_VARIABLE_LIMIT {
+        let compiled = CompiledEvaluator::new(evaluator, &variables).expect("a formula's own variables occur in it");
+        return compiled.output_column().iter().map(|word| word.count_ones() as u128).sum();
+    }
+    // Tseitin variables are functions of the original ones, so they do not change the count.
+    let encoding = Encoding::tseitin(evaluator);
+    let all = (0..encoding.formula().variable_count()).collect::<Vec<usize>>();
+    count_cnf(encoding.formula(), &all)
+}
+
+/// Number of assignments of `projection` that extend to a model of `evaluator`.
+pub fn count_projected_models<S: AsRef<str>>(evaluator: &Evaluator, projection: &[S]) -> Result<u128, FormulaError> {
+    let variables = evaluator.variable_table();
+    if variables.len() <= TABLE_VARIABLE_LIMIT {
+        return Ok(projected_models(evaluator, projection)?.count() as u128);
+    }
+    let mut indices = projection
+        .iter()
+        .map(|name| {
+            variables
+                .index(name.as_ref())
+                .ok_or_else(|| FormulaError::UnknownVariable { variable: name.as_ref().to_string() })
+        })
+        .collect::<Result<Vec<usize>, FormulaError>>()?;
+    indices.sort();
+    indices.dedup();
+    Ok(count_cnf(Encoding::plaisted_greenbaum(evaluator).formula(), &indices))
+}
+
+/// Number of assignments of the variables in `projection` that extend to a model of `formula`,
+/// found by a DPLL counter that splits the clauses into independent components and caches
+/// the count of each component it meets.
+pub fn count_cnf(formula: &CnfFormula, projection: &[usize]) -> u128 {
+    let mut projection = projection.to_vec();
+    projection.sort();
+    projection.dedup();
+    let mut projected = vec![false; formula.variable_count()];
+    for &variable in &projection {
+        projected[variable] = true;
+    }
+    let mut counter = Counter { projected, cache: HashMap::new() };
+    let clauses = formula.clauses().iter().map(|clause| normalise(clause.clone())).collect::<Vec<_>>();
+    counter.count_branch(&clauses, None, projection.len())
+}
+
+fn normalise(mut clause: Vec<Lit>) -> Vec<Lit> {
+    clause.sort();
+    clause.dedup();
+    clause
+}
+
+struct Counter {
+    projected: Vec<bool>,
+    cache: HashMap<Vec<Vec<Lit>>, u128>,
+}
+
+impl Counter {
+    /// Count over the projected variables of `clauses` once `decision` holds, where `scope`
+    /// is how many projected variables besides `decision` the count ranges over.
+    fn count_branch(&mut self, clauses: &[Vec<Lit>], decision: Option<Lit>, scope: usize) -> u128 {
+        let Some((residual, assigned)) = propagate(clauses, decision) else {
+            return 0;
+        };
+        let fixed = assigned
+            .keys()
+            .filter(|&&variable| self.projected[variable] && Some(variable) != decision.map(Lit::variable))
+            .count();
+        let components = components(residual);
+        let remaining = components
+            .iter()
+            .map(|component| self.projected_variables(component).len())
+            .sum::<usize>();
+        let free = scope - fixed - remaining;
+
+        assert!(free < 128, "model count overflows u128");
+        let mut count = 1u128 << free;
+        for component in components {
+            if count == 0 {
+                break;
+            }
+            count = count.checked_mul(self.count_component(component)).expect("model count overflows u128");
+        }
+        count
+    }
+
+    /// Count of a connected component over its own projected variables.
+    fn count_component(&mut self, mut clauses: Vec<Vec<Lit>>) -> u128 {
+        clauses.sort();
+        if let Some(&count) = self.cache.get(&clauses) {
+            return count;
+        }
+        let variables = self.projected_variables(&clauses);
+        let count = if variables.is_empty() {
+            // Only unprojected variables are left, whose values do not multiply the count.
+            let mut formula = CnfFormula::new(0);
+            for clause in &clauses {
+                formula.add_clause(clause.clone());
+            }
+            cdcl::solve(&formula).is_sat() as u128
+        } else {
+            let mut occurrences = BTreeMap::new();
+            for lit in clauses.iter().flatten().filter(|lit| self.projected[lit.variable()]) {
+                *occurrences.entry(lit.variable()).or_insert(0) += 1;
+            }
+            let branch = occurrences.iter().max_by_key(|(_, &count)| count).map(|(&variable, _)| variable).unwrap();
+            let scope = variables.len() - 1;
+            [Lit::positive(branch), Lit::negative(branch)]
+                .into_iter()
+                .map(|decision| self.count_branch(&clauses, Some(decision), scope))
+                .try_fold(0u128, u128::checked_add)
+                .expect("model count overflows u128")
+        };
+        self.cache.insert(clauses, count);
+        count
+    }
+
+    fn projected_variables(&self, clauses: &[Vec<Lit>]) -> Vec<usize> {
+        let mut variables = clauses
+            .iter()
+            .flatten()
+            .map(|lit| lit.variable())
+            .filter(|&variable| self.projected[variable])
+            .collect::<Vec<usize>>();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+}
+
+/// Clauses left open and the variables assigned while simplifying.
+type Residual = (Vec<Vec<Lit>>, HashMap<usize, bool>);
+
+/// Simplifies `clauses` under `decision` and the unit clauses it leads to, returning the
+/// clauses left open and the assigned variables, or `None` on a conflict.
+fn propagate(clauses: &[Vec<Lit>], decision: Option<Lit>) -> Option<Residual> {
+    let mut assigned = HashMap::new();
+    let mut pending = decision.into_iter().collect::<Vec<Lit>>();
+    let mut clauses = clauses.to_vec();
+    loop {
+        for lit in pending.drain(..) {
+            if assigned.insert(lit.variable(), !lit.is_negated()) == Some(lit.is_negated()) {
+                return None;
+            }
+        }
+        let value = |lit: &Lit| assigned.get(&lit.variable()).map(|&value| lit.apply(value));
+        let mut open = vec![];
+        for clause in clauses {
+            if clause.iter().any(|lit| value(lit) == Some(true)) {
+                continue;
+            }
+            let clause = clause.into_iter().filter(|lit| value(lit).is_none()).collect::<Vec<Lit>>();
+            match clause.len() {
+                0 => return None,
+                1 => pending.push(clause[0]),
+                _ => open.push(clause),
+            }
+        }
+        clauses = open;
+        if pending.is_empty() {
+            return Some((clauses, assigned));
+        }
+    }
+}
+
+/// Groups clauses into sets sharing no variable.
+fn components(clauses: Vec<Vec<Lit>>) -> Vec<Vec<Vec<Lit>>> {
+    fn find(parents: &mut HashMap<usize, usize>, variable: usize) -> usize {
+        let parent = *parents.entry(variable).or_insert(variable);
+        if parent == variable {
+            return variable;
+        }
+        let root = find(parents, parent);
+        parents.insert(variable, root);
+        root
+    }
+
+    let mut parents = HashMap::new();
+    for clause in &clauses {
+        let first = find(&mut parents, clause[0].variable());
+        for lit in &clause[1..] {
+            let root = find(&mut parents, lit.variable());
+            parents.insert(root, first);
+        }
+    }
+    let mut groups: BTreeMap<usize, Vec<Vec<Lit>>> = BTreeMap::new();
+    for clause in clauses {
+        let root = find(&mut parents, clause[0].variable());
+        groups.entry(root).or_default().push(clause);
+    }
+    groups.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(count: usize, operator: &str) -> String {
+        let names = (0..count).map(|i| format!("x{i:03}")).collect::<Vec<String>>();
+        names.join(" ") + &format!(" {operator}").repeat(count - 1)
+    }
+
+    #[test]
+    fn test_count_small_formulas() {
+        assert_eq!(count_models(&Evaluator::new("AB|")), 3);
+        assert_eq!(count_models(&Evaluator::new("AA!&")), 0);
+        assert_eq!(count_models(&Evaluator::new("1")), 1);
+        assert_eq!(count_projected_models(&Evaluator::new("AB|C&"), &["A", "C"]), Ok(2));
+    }
+
+    #[test]
+    fn test_count_large_formulas() {
+        assert_eq!(count_models(&Evaluator::new(&chain(100, "|"))), (1 << 100) - 1);
+        assert_eq!(count_models(&Evaluator::new(&chain(40, "^"))), 1 << 39);
+        assert_eq!(count_models(&Evaluator::new(&chain(127, "&"))), 1);
+        let pairs = (0..30).map(|i| format!("a{i:02} b{i:02} |")).collect::<Vec<String>>().join(" ");
+        assert_eq!(count_models(&Evaluator::new(&(pairs + &" &".repeat(29)))), 3u128.pow(30));
+    }
+
+    #[test]
+    fn test_count_agrees_with_truth_table() {
+        for formula in ["ABC?DE^|F=G>", "AB/CD#%EEF<?G&", "A B C D E F G H I J K L M N & & & & & & | | | | ^ ^ ="] {
+            let evaluator = Evaluator::new(formula);
+            let encoding = Encoding::tseitin(&evaluator);
+            let all = (0..encoding.formula().variable_count()).collect::<Vec<usize>>();
+            let table = CompiledEvaluator::new(&evaluator, &evaluator.variable_table()).unwrap();
+            let expected = table.output_column().iter().map(|word| word.count_ones() as u128).sum::<u128>();
+            assert_eq!(count_cnf(encoding.formula(), &all), expected, "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_projected_count_of_large_formula() {
+        let evaluator = Evaluator::new(&format!("{} x000 x001 ^ &", chain(20, "|")));
+        assert_eq!(count_projected_models(&evaluator, &["x000", "x001", "x005"]), Ok(4));
+        assert_eq!(count_projected_models(&evaluator, &Vec::<String>::new()), Ok(1));
+        let encoding = Encoding::plaisted_greenbaum(&evaluator);
+        assert_eq!(count_cnf(encoding.formula(), &[0, 1, 0, 1]), count_cnf(encoding.formula(), &[0, 1]));
+        assert_eq!(count_cnf(&CnfFormula::new(3), &[2, 2, 0]), 4);
+        assert_eq!(
+            count_projected_models(&evaluator, &["y"]),
+            Err(FormulaError::UnknownVariable { variable: "y".to_string() })
+        );
+    }
+}
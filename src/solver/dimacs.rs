@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::io;
+
+use crate::boolean_evaluator::{boolean_evaluator::Evaluator, variables::VariableTable};
+use crate::error::DimacsError;
+
+use super::cnf::{CnfFormula, Lit};
+
+/// Reads a DIMACS `p cnf` problem. Clauses may span lines, `%` ends the input as in SATLIB
+/// files, and `c var <number> <name>` comments written by `write_dimacs` name the variables.
+/// Variables without such a comment, or whose name is not an identifier or is already taken,
+/// are named `x<number>`, with a `_<k>` suffix when that name is taken too.
+pub fn parse_dimacs(input: &str) -> Result<(CnfFormula, VariableTable), DimacsError> {
+    let mut header: Option<(usize, usize)> = None;
+    let mut names: HashMap<usize, &str> = HashMap::new();
+    let mut formula = CnfFormula::new(0);
+    let mut clause = vec![];
+    let mut last_line = 0;
+
+    for (number, line) in input.lines().enumerate() {
+        let line_number = number + 1;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            None => continue,
+            Some("%") => break,
+            Some("c") => {
+                if let (Some("var"), Some(variable), Some(name), None) =
+                    (tokens.next(), tokens.next(), tokens.next(), tokens.next())
+                {
+                    if let (Ok(variable), true) = (variable.parse(), is_identifier(name)) {
+                        names.insert(variable, name);
+                    }
+                }
+                continue;
+            }
+            Some("p") => {
+                let parsed = match (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+                    (Some("cnf"), Some(variables), Some(clauses), None) => variables
+                        .parse()
+                        .ok()
+                        .filter(|&variables| variables <= Lit::MAX_VARIABLE + 1)
+                        .zip(clauses.parse().ok()),
+                    _ => None,
+                };
+                match (header, parsed) {
+                    (None, Some(parsed)) => {
+                        header = Some(parsed);
+                        formula = CnfFormula::new(parsed.0);
+                    }
+                    _ => return Err(DimacsError::InvalidHeader { line: line_number }),
+                }
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        let Some((variable_count, _)) = header else {
+            return Err(DimacsError::MissingHeader { line: line_number });
+        };
+        for token in line.split_whitespace() {
+            let value: i64 = token
+                .parse()
+                .map_err(|_| DimacsError::InvalidLiteral { line: line_number, token: token.to_string() })?;
+            if value == 0 {
+                formula.add_clause(std::mem::take(&mut clause));
+            } else if value.unsigned_abs() as usize > variable_count {
+                return Err(DimacsError::VariableOutOfRange {
+                    line: line_number,
+                    variable: value.unsigned_abs(),
+                    limit: variable_count,
+                });
+            } else {
+                clause.push(Lit::from_dimacs(value));
+            }
+        }
+        last_line = line_number;
+    }
+
+    if !clause.is_empty() {
+        return Err(DimacsError::UnterminatedClause { line: last_line });
+    }
+    let Some((variable_count, clause_count)) = header else {
+        return Err(DimacsError::MissingHeader { line: input.lines().count() + 1 });
+    };
+    if formula.clauses().len() != clause_count {
+        return Err(DimacsError::ClauseCountMismatch { expected: clause_count, found: formula.clauses().len() });
+    }
+
+    let mut variables = VariableTable::new();
+    for number in 1..=variable_count {
+        let name = names.get(&number).map(|name| name.to_string()).unwrap_or_else(|| format!("x{number}"));
+        // A repeated name would merge two variables; keep them apart under a fresh default name.
+        let mut candidates = [name, format!("x{number}")]
+            .into_iter()
+            .chain((1..).map(|suffix| format!("x{number}_{suffix}")));
+        while candidates.next().is_some_and(|candidate| variables.intern(&candidate) != number - 1) {}
+    }
+    Ok((formula, variables))
+}
+
+/// Whether `name` reads back as a single variable, in RPN as in infix.
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Writes `formula` in DIMACS, preceded by a `c var <number> <name>` line per variable.
+pub fn write_dimacs(formula: &CnfFormula, variables: &VariableTable, out: &mut dyn io::Write) -> io::Result<()> {
+    for (index, name) in variables.names().iter().enumerate() {
+        writeln!(out, "c var {} {name}", index + 1)?;
+    }
+    writeln!(out, "p cnf {} {}", formula.variable_count(), formula.clauses().len())?;
+    for clause in formula.clauses() {
+        for lit in clause {
+            write!(out, "{} ", lit.to_dimacs())?;
+        }
+        writeln!(out, "0")?;
+    }
+    Ok(())
+}
+
+impl Evaluator {
+    /// Reads a DIMACS problem as the conjunction of its clauses.
+    pub fn from_dimacs(input: &str) -> Result<Self, DimacsError> {
+        let (formula, variables) = parse_dimacs(input)?;
+        Ok(formula.to_evaluator(&variables))
+    }
+
+    /// Writes `to_conjunctive_normal_form` in DIMACS, numbering the variables in sorted name order.
+    pub fn write_dimacs(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        let (formula, variables) = CnfFormula::from_evaluator(&self.to_conjunctive_normal_form());
+        write_dimacs(&formula, &variables, out)
+    }
+
+    pub fn to_dimacs(&self) -> String {
+        let mut out = vec![];
+        self.write_dimacs(&mut out).expect("writing to a Vec cannot fail");
+        String::from_utf8(out).expect("DIMACS output is UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dimacs() {
+        let input = "c example\np cnf 3 2\n1 -3\n 0 2 3 -1 0\n";
+        let (formula, variables) = parse_dimacs(input).unwrap();
+        assert_eq!(formula.variable_count(), 3);
+        assert_eq!(
+            formula.clauses(),
+            [
+                vec![Lit::from_dimacs(1), Lit::from_dimacs(-3)],
+                vec![Lit::from_dimacs(2), Lit::from_dimacs(3), Lit::from_dimacs(-1)],
+            ]
+        );
+        assert_eq!(variables.names(), ["x1", "x2", "x3"]);
+        assert_eq!(parse_dimacs("p cnf 2 1\n1 2 0\n%\n0\n").unwrap().0.clauses().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_dimacs_errors() {
+        assert_eq!(parse_dimacs("1 2 0\n"), Err(DimacsError::MissingHeader { line: 1 }));
+        assert_eq!(parse_dimacs("p cnf 2\n"), Err(DimacsError::InvalidHeader { line: 1 }));
+        assert_eq!(parse_dimacs("p cnf 2 1\np cnf 2 1\n"), Err(DimacsError::InvalidHeader { line: 2 }));
+        assert_eq!(
+            parse_dimacs("p cnf 2 1\n1 a 0\n"),
+            Err(DimacsError::InvalidLiteral { line: 2, token: "a".to_string() })
+        );
+        assert_eq!(
+            parse_dimacs("p cnf 2 1\n1 -3 0\n"),
+            Err(DimacsError::VariableOutOfRange { line: 2, variable: 3, limit: 2 })
+        );
+        assert_eq!(parse_dimacs("p cnf 4294967296 1\n"), Err(DimacsError::InvalidHeader { line: 1 }));
+        assert_eq!(parse_dimacs("p cnf 2 1\n1 2\n"), Err(DimacsError::UnterminatedClause { line: 2 }));
+        assert_eq!(
+            parse_dimacs("p cnf 2 2\n1 2 0\n"),
+            Err(DimacsError::ClauseCountMismatch { expected: 2, found: 1 })
+        );
+    }
+
+    #[test]
+    fn test_write_dimacs_round_trip() {
+        let evaluator = Evaluator::from_infix("(door_open -> alarm) & !(alarm & muted)").unwrap();
+        let dimacs = evaluator.to_dimacs();
+        assert_eq!(
+            dimacs,
+            "c var 1 alarm\nc var 2 door_open\nc var 3 muted\np cnf 3 2\n1 -2 0\n-1 -3 0\n"
+        );
+        let parsed = Evaluator::from_dimacs(&dimacs).unwrap();
+        assert_eq!(parsed.variables(), evaluator.variables());
+        assert_eq!(parsed.to_infix(), "(alarm | !door_open) & (!alarm | !muted)");
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(Evaluator::new("1").to_dimacs(), "p cnf 0 0\n");
+        assert_eq!(Evaluator::from_dimacs("p cnf 0 0\n"), Ok(Evaluator::True));
+        assert_eq!(Evaluator::from_dimacs("p cnf 1 1\n0\n"), Ok(Evaluator::False));
+    }
+
+    #[test]
+    fn test_colliding_and_invalid_names() {
+        let names = |input: &str| parse_dimacs(input).unwrap().1.names().to_vec();
+        assert_eq!(names("c var 1 x2\np cnf 2 1\n1 2 0\n"), ["x2", "x2_1"]);
+        assert_eq!(names("c var 1 a\nc var 2 a\nc var 3 x2\np cnf 3 0\n"), ["a", "x2", "x3"]);
+        assert_eq!(names("c var 2 x1\nc var 3 x1_1\np cnf 3 0\n"), ["x1", "x2", "x1_1"]);
+        assert_eq!(names("c var 1 &\nc var 2 1\nc var 3 _ok\np cnf 3 0\n"), ["x1", "x2", "_ok"]);
+
+        let evaluator = Evaluator::from_dimacs("c var 1 x2\np cnf 2 1\n1 2 0\n").unwrap();
+        assert_eq!(evaluator.variables(), ["x2", "x2_1"]);
+        let evaluator = Evaluator::from_dimacs("c var 1 &\nc var 2 b\np cnf 2 1\n-1 2 0\n").unwrap();
+        assert_eq!(Evaluator::new(&evaluator.to_string()), evaluator);
+        assert_eq!(Evaluator::from_infix(&evaluator.to_infix()), Ok(evaluator));
+    }
+}
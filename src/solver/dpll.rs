@@ -0,0 +1,191 @@
+use super::cnf::{CnfFormula, Lit, SolveResult};
+
+/// Decides `formula` with the Davis–Putnam–Logemann–Loveland procedure: unit propagation,
+/// pure literal elimination, and branching on the literal occurring most often in the
+/// clauses that are still open. Variables left free by the search are set to `false`.
+pub fn solve(formula: &CnfFormula) -> SolveResult {
+    let mut search = Search { formula, values: vec![None; formula.variable_count()], trail: vec![] };
+    if search.run() {
+        SolveResult::Sat(search.values.iter().map(|value| value.unwrap_or(false)).collect())
+    } else {
+        SolveResult::Unsat
+    }
+}
+
+enum ClauseState {
+    Satisfied,
+    Falsified,
+    Unit(Lit),
+    Open,
+}
+
+struct Search<'a> {
+    formula: &'a CnfFormula,
+    values: Vec<Option<bool>>,
+    /// Assigned variables in assignment order, so a branch can be undone.
+    trail: Vec<usize>,
+}
+
+impl Search<'_> {
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.variable()].map(|value| lit.apply(value))
+    }
+
+    fn assign(&mut self, lit: Lit) {
+        self.values[lit.variable()] = Some(!lit.is_negated());
+        self.trail.push(lit.variable());
+    }
+
+    fn undo(&mut self, length: usize) {
+        for variable in self.trail.drain(length..) {
+            self.values[variable] = None;
+        }
+    }
+
+    fn state(&self, clause: &[Lit]) -> ClauseState {
+        let mut unassigned = None;
+        let mut count = 0;
+        for &lit in clause {
+            match self.value(lit) {
+                Some(true) => return ClauseState::Satisfied,
+                Some(false) => {}
+                None => {
+                    unassigned = Some(lit);
+                    count += 1;
+                }
+            }
+        }
+        match (count, unassigned) {
+            (0, _) => ClauseState::Falsified,
+            (1, Some(lit)) => ClauseState::Unit(lit),
+            _ => ClauseState::Open,
+        }
+    }
+
+    /// Assigns unit literals until a fixpoint, returning `false` on a falsified clause.
+    fn propagate(&mut self) -> bool {
+        loop {
+            let mut changed = false;
+            for clause in self.formula.clauses() {
+                match self.state(clause) {
+                    ClauseState::Falsified => return false,
+                    ClauseState::Unit(lit) => {
+                        self.assign(lit);
+                        changed = true;
+                    }
+                    ClauseState::Satisfied | ClauseState::Open => {}
+                }
+            }
+            if !changed {
+                return true;
+            }
+        }
+    }
+
+    /// Occurrences of each unassigned literal in open clauses, indexed by `Lit::code`.
+    fn occurrences(&self) -> Vec<usize> {
+        let mut occurrences = vec![0; 2 * self.values.len()];
+        for clause in self.formula.clauses() {
+            if let ClauseState::Open = self.state(clause) {
+                for &lit in clause {
+                    if self.value(lit).is_none() {
+                        occurrences[lit.code()] += 1;
+                    }
+                }
+            }
+        }
+        occurrences
+    }
+
+    /// Assigns every literal whose negation no open clause contains. Such assignments only
+    /// satisfy clauses, so they cannot cause a conflict.
+    fn eliminate_pure_literals(&mut self, occurrences: &[usize]) {
+        for variable in 0..self.values.len() {
+            let (positive, negative) = (Lit::positive(variable), Lit::negative(variable));
+            match (occurrences[positive.code()], occurrences[negative.code()]) {
+                (0, 0) => {}
+                (_, 0) => self.assign(positive),
+                (0, _) => self.assign(negative),
+                _ => {}
+            }
+        }
+    }
+
+    fn run(&mut self) -> bool {
+        let start = self.trail.len();
+        if !self.propagate() {
+            self.undo(start);
+            return false;
+        }
+        let occurrences = self.occurrences();
+        self.eliminate_pure_literals(&occurrences);
+
+        let branch = (0..occurrences.len())
+            .filter(|&code| self.values[code / 2].is_none() && occurrences[code] > 0)
+            .max_by_key(|&code| occurrences[code]);
+        let Some(code) = branch else {
+            // Propagation left no clause falsified and no open clause has a free literal.
+            return true;
+        };
+        let lit = Lit::new(code / 2, code % 2 == 1);
+
+        for decision in [lit, !lit] {
+            let length = self.trail.len();
+            self.assign(decision);
+            if self.run() {
+                return true;
+            }
+            self.undo(length);
+        }
+        self.undo(start);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boolean_evaluator::boolean_evaluator::Evaluator;
+
+    fn clause(lits: &[i32]) -> Vec<Lit> {
+        lits.iter().map(|&l| Lit::new(l.unsigned_abs() as usize - 1, l < 0)).collect()
+    }
+
+    #[test]
+    fn test_solve_returns_model() {
+        let mut formula = CnfFormula::new(3);
+        for lits in [&[1, 2][..], &[-1, 3], &[-3, -2], &[-2]] {
+            formula.add_clause(clause(lits));
+        }
+        let result = solve(&formula);
+        assert!(formula.is_satisfied_by(result.model().unwrap()));
+    }
+
+    #[test]
+    fn test_solve_unsat() {
+        let mut formula = CnfFormula::new(2);
+        for lits in [[1, 2], [-1, 2], [1, -2], [-1, -2]] {
+            formula.add_clause(clause(&lits));
+        }
+        assert_eq!(solve(&formula), SolveResult::Unsat);
+        assert_eq!(solve(&CnfFormula::from_evaluator(&Evaluator::new("0")).0), SolveResult::Unsat);
+        assert!(solve(&CnfFormula::new(0)).is_sat());
+    }
+
+    #[test]
+    fn test_pigeonhole() {
+        // Four pigeons in three holes: variable 3 * p + h puts pigeon p in hole h.
+        let mut formula = CnfFormula::new(12);
+        for p in 0..4 {
+            formula.add_clause((0..3).map(|h| Lit::positive(3 * p + h)).collect());
+        }
+        for h in 0..3 {
+            for p in 0..4 {
+                for q in p + 1..4 {
+                    formula.add_clause(vec![Lit::negative(3 * p + h), Lit::negative(3 * q + h)]);
+                }
+            }
+        }
+        assert_eq!(solve(&formula), SolveResult::Unsat);
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use std::io;
+
+use crate::error::DratError;
+
+use super::cdcl::{Solver, WatchedClauses};
+use super::cnf::{CnfFormula, Lit, SolveResult};
+
+/// Encodings of a DRAT proof, as read by `drat-trim` and the checker below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofFormat {
+    /// One step per line: `l1 l2 … 0` adds a lemma, `d l1 l2 … 0` deletes a clause.
+    Text,
+    /// `a` or `d` followed by literals `2 * (variable + 1) + negated` as little-endian base-128
+    /// varints, then a 0 byte.
+    Binary,
+}
+
+/// Step of a DRAT proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofStep {
+    Add(Vec<Lit>),
+    Delete(Vec<Lit>),
+}
+
+impl ProofFormat {
+    pub fn write_step(self, out: &mut dyn io::Write, step: &ProofStep) -> io::Result<()> {
+        let (tag, lits) = match step {
+            ProofStep::Add(lits) => (b'a', lits),
+            ProofStep::Delete(lits) => (b'd', lits),
+        };
+        match self {
+            ProofFormat::Text => {
+                if tag == b'd' {
+                    write!(out, "d ")?;
+                }
+                for lit in lits {
+                    write!(out, "{} ", lit.to_dimacs())?;
+                }
+                writeln!(out, "0")
+            }
+            ProofFormat::Binary => {
+                let mut bytes = vec![tag];
+                for lit in lits {
+                    let mut value = 2 * (lit.variable() as u64 + 1) + lit.is_negated() as u64;
+                    while value >= 0x80 {
+                        bytes.push(value as u8 | 0x80);
+                        value >>= 7;
+                    }
+                    bytes.push(value as u8);
+                }
+                bytes.push(0);
+                out.write_all(&bytes)
+            }
+        }
+    }
+
+    pub fn parse(self, proof: &[u8]) -> Result<Vec<ProofStep>, DratError> {
+        match self {
+            ProofFormat::Text => parse_text(proof),
+            ProofFormat::Binary => parse_binary(proof),
+        }
+    }
+}
+
+fn parse_text(proof: &[u8]) -> Result<Vec<ProofStep>, DratError> {
+    let text = std::str::from_utf8(proof).map_err(|_| DratError::Malformed { position: 1 })?;
+    let mut steps = vec![];
+    let mut lits = vec![];
+    let mut delete = false;
+    for (number, line) in text.lines().enumerate() {
+        let malformed = DratError::Malformed { position: number + 1 };
+        for token in line.split_whitespace() {
+            if token == "c" {
+                break;
+            }
+            if token == "d" && lits.is_empty() && !delete {
+                delete = true;
+                continue;
+            }
+            match token.parse::<i64>().map_err(|_| malformed.clone())? {
+                value if value.unsigned_abs() > Lit::MAX_VARIABLE as u64 + 1 => return Err(malformed),
+                0 => {
+                    let lits = std::mem::take(&mut lits);
+                    steps.push(if delete { ProofStep::Delete(lits) } else { ProofStep::Add(lits) });
+                    delete = false;
+                }
+                value => lits.push(Lit::from_dimacs(value)),
+            }
+        }
+    }
+    if !lits.is_empty() || delete {
+        return Err(DratError::Malformed { position: text.lines().count() });
+    }
+    Ok(steps)
+}
+
+fn parse_binary(proof: &[u8]) -> Result<Vec<ProofStep>, DratError> {
+    let mut steps = vec![];
+    let mut position = 0;
+    while position < proof.len() {
+        let tag = proof[position];
+        if tag != b'a' && tag != b'd' {
+            return Err(DratError::Malformed { position });
+        }
+        position += 1;
+        let mut lits = vec![];
+        loop {
+            let mut value = 0u64;
+            let mut shift = 0;
+            loop {
+                let byte = *proof.get(position).ok_or(DratError::Malformed { position })?;
+                position += 1;
+                value |= ((byte & 0x7F) as u64) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                if shift > 56 {
+                    return Err(DratError::Malformed { position });
+                }
+            }
+            match value {
+                0 => break,
+                1 => return Err(DratError::Malformed { position: position - 1 }),
+                value if value / 2 - 1 > Lit::MAX_VARIABLE as u64 => {
+                    return Err(DratError::Malformed { position: position - 1 });
+                }
+                value => lits.push(Lit::new((value / 2 - 1) as usize, value % 2 == 1)),
+            }
+        }
+        steps.push(if tag == b'a' { ProofStep::Add(lits) } else { ProofStep::Delete(lits) });
+    }
+    Ok(steps)
+}
+
+/// Decides `formula` with a fresh `Solver`, writing its DRAT proof to `out` as the solver
+/// goes.
+pub fn solve_with_proof(formula: &CnfFormula, out: &mut dyn io::Write, format: ProofFormat) -> io::Result<SolveResult> {
+    let mut solver = Solver::new(formula);
+    solver.log_proof(Box::new(out), format);
+    let result = solver.solve();
+    solver.finish_proof()?;
+    Ok(result)
+}
+
+/// Checks that `proof` refutes `formula`: every lemma the refutation depends on must be a
+/// reverse unit propagation (RUP) or resolution asymmetric tautology (RAT) consequence of
+/// the clauses present before it. Lemmas are checked backwards from the empty clause, so
+/// the ones the refutation does not use are never checked. Deletions of clauses that are
+/// not present are ignored, like `drat-trim` does.
+pub fn check_proof(formula: &CnfFormula, proof: &[ProofStep]) -> Result<(), DratError> {
+    let mut checker = Checker::default();
+    for clause in formula.clauses() {
+        checker.push(clause);
+    }
+
+    // Forward pass up to the first empty lemma, recording the clause each step touches.
+    let mut steps: Vec<(usize, bool)> = vec![];
+    let mut lemma_steps = HashMap::new();
+    for (number, step) in proof.iter().enumerate() {
+        match step {
+            ProofStep::Add(lits) => {
+                let id = checker.push(lits);
+                lemma_steps.insert(id, number);
+                steps.push((id, true));
+                if lits.is_empty() {
+                    break;
+                }
+            }
+            ProofStep::Delete(lits) => {
+                if let Some(id) = checker.find(lits) {
+                    checker.set_active(id, false);
+                    steps.push((id, false));
+                }
+            }
+        }
+    }
+    let refuted = matches!(steps.last(), Some(&(id, true)) if checker.clauses[id].is_empty());
+    if !refuted {
+        // Without an explicit empty clause the final clauses must propagate to a conflict.
+        let id = checker.push(&[]);
+        lemma_steps.insert(id, proof.len());
+        steps.push((id, true));
+    }
+    let last = steps.last().unwrap().0;
+    checker.marked[last] = true;
+
+    for &(id, added) in steps.iter().rev() {
+        checker.set_active(id, !added);
+        if !added {
+            continue;
+        }
+        if checker.marked[id] && !checker.verify(id) {
+            return Err(if id == last && !refuted {
+                DratError::NoRefutation
+            } else {
+                let step = lemma_steps[&id];
+                let ProofStep::Add(lits) = &proof[step] else { unreachable!("lemmas come from additions") };
+                DratError::InvalidLemma { step, clause: lits.iter().map(|lit| lit.to_dimacs()).collect() }
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Parses `proof` in `format` and checks it against `formula` with `check_proof`.
+pub fn check_drat(formula: &CnfFormula, proof: &[u8], format: ProofFormat) -> Result<(), DratError> {
+    check_proof(formula, &format.parse(proof)?)
+}
+
+#[derive(Debug, Default)]
+struct Checker {
+    /// Literals of each clause, reordered as its watches move.
+    clauses: Vec<Vec<Lit>>,
+    /// First literal of each clause as given, the pivot of its RAT check.
+    pivots: Vec<Option<Lit>>,
+    active: Vec<bool>,
+    /// Clauses the refutation depends on, found while verifying the lemmas after them.
+    marked: Vec<bool>,
+    /// Active clause ids by sorted literals, for deletions.
+    index: HashMap<Vec<Lit>, Vec<usize>>,
+    /// Active clauses watching each literal, indexed by `Lit::code`.
+    watches: Vec<Vec<usize>>,
+    /// Clauses with fewer than two literals, which have nothing to watch.
+    short: Vec<usize>,
+    values: Vec<Option<bool>>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+    /// Variables met while walking back from a conflict.
+    seen: Vec<bool>,
+}
+
+impl Checker {
+    fn push(&mut self, lits: &[Lit]) -> usize {
+        let id = self.clauses.len();
+        // Repeated literals would hide units.
+        let mut clause: Vec<Lit> = vec![];
+        for &lit in lits {
+            if !clause.contains(&lit) {
+                clause.push(lit);
+            }
+            while self.values.len() <= lit.variable() {
+                self.values.push(None);
+                self.reasons.push(None);
+                self.seen.push(false);
+                self.watches.extend([vec![], vec![]]);
+            }
+        }
+        let mut key = clause.clone();
+        key.sort();
+        self.index.entry(key).or_default().push(id);
+        if clause.len() < 2 {
+            self.short.push(id);
+        }
+        self.pivots.push(clause.first().copied());
+        self.clauses.push(clause);
+        self.active.push(false);
+        self.marked.push(false);
+        self.set_active(id, true);
+        id
+    }
+
+    fn find(&mut self, lits: &[Lit]) -> Option<usize> {
+        let mut key = lits.to_vec();
+        key.sort();
+        key.dedup();
+        let ids = self.index.get_mut(&key)?;
+        let position = ids.iter().rposition(|&id| self.active[id])?;
+        Some(ids.remove(position))
+    }
+
+    /// Adds clause `id` to the watch lists of its first two literals, or removes it.
+    fn set_active(&mut self, id: usize, active: bool) {
+        self.active[id] = active;
+        if let [first, second, ..] = self.clauses[id][..] {
+            for lit in [first, second] {
+                let watchers = &mut self.watches[lit.code()];
+                if active {
+                    watchers.push(id);
+                } else {
+                    watchers.retain(|&other| other != id);
+                }
+            }
+        }
+    }
+
+    /// Whether the inactive clause `id` is RUP or RAT over the active clauses, marking the
+    /// clauses its derivation uses.
+    fn verify(&mut self, id: usize) -> bool {
+        let lemma = self.clauses[id].clone();
+        if self.rup(&lemma) {
+            return true;
+        }
+        let Some(pivot) = self.pivots[id] else {
+            return false;
+        };
+        let candidates = (0..self.clauses.len())
+            .filter(|&other| self.active[other] && self.clauses[other].contains(&!pivot))
+            .collect::<Vec<usize>>();
+        for other in candidates {
+            let mut resolvent = lemma.clone();
+            resolvent.extend(self.clauses[other].iter().copied().filter(|&lit| lit != !pivot));
+            if !resolvent.iter().any(|&lit| resolvent.contains(&!lit)) && !self.rup(&resolvent) {
+                return false;
+            }
+            self.marked[other] = true;
+        }
+        true
+    }
+
+    /// Reverse unit propagation: assigns the negation of `lemma` and propagates the active
+    /// clauses. On a conflict, marks the clauses involved and returns `true`.
+    fn rup(&mut self, lemma: &[Lit]) -> bool {
+        let mut tautology = false;
+        for &lit in lemma {
+            match self.value(lit) {
+                Some(true) => tautology = true,
+                Some(false) => {}
+                None => self.assign(!lit, None),
+            }
+        }
+        let conflict = if tautology { None } else { self.propagate() };
+        if let Some(conflict) = conflict {
+            self.mark_conflict(conflict);
+        }
+        for lit in std::mem::take(&mut self.trail) {
+            self.values[lit.variable()] = None;
+        }
+        tautology || conflict.is_some()
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        self.values[lit.variable()] = Some(!lit.is_negated());
+        self.reasons[lit.variable()] = reason;
+        self.trail.push(lit);
+    }
+
+    /// Propagates the trail and the active unit clauses, returning a falsified clause.
+    fn propagate(&mut self) -> Option<usize> {
+        for i in 0..self.short.len() {
+            let id = self.short[i];
+            if !self.active[id] {
+                continue;
+            }
+            let Some(&unit) = self.clauses[id].first() else {
+                return Some(id);
+            };
+            match self.value(unit) {
+                Some(false) => return Some(id),
+                Some(true) => {}
+                None => self.assign(unit, Some(id)),
+            }
+        }
+        let mut propagated = 0;
+        while propagated < self.trail.len() {
+            let false_lit = !self.trail[propagated];
+            propagated += 1;
+            if let Some(conflict) = self.visit_watchers(false_lit) {
+                return Some(conflict);
+            }
+        }
+        None
+    }
+
+    /// Marks `conflict` and every reason clause of the implication graph leading to it,
+    /// whether or not an earlier check already marked it.
+    fn mark_conflict(&mut self, conflict: usize) {
+        self.marked[conflict] = true;
+        for lit in &self.clauses[conflict] {
+            self.seen[lit.variable()] = true;
+        }
+        for position in (0..self.trail.len()).rev() {
+            let variable = self.trail[position].variable();
+            if !self.seen[variable] {
+                continue;
+            }
+            if let Some(reason) = self.reasons[variable] {
+                self.marked[reason] = true;
+                for lit in &self.clauses[reason] {
+                    self.seen[lit.variable()] = true;
+                }
+            }
+            self.seen[variable] = false;
+        }
+    }
+}
+
+impl WatchedClauses for Checker {
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.variable()].map(|value| lit.apply(value))
+    }
+
+    fn lits_mut(&mut self, clause: usize) -> &mut [Lit] {
+        &mut self.clauses[clause]
+    }
+
+    fn watchers_mut(&mut self, lit: Lit) -> &mut Vec<usize> {
+        &mut self.watches[lit.code()]
+    }
+
+    fn imply(&mut self, lit: Lit, reason: usize) {
+        self.assign(lit, Some(reason));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clauses(clauses: &[&[i64]]) -> Vec<Vec<Lit>> {
+        clauses.iter().map(|clause| clause.iter().map(|&value| Lit::from_dimacs(value)).collect()).collect()
+    }
+
+    fn formula(list: &[&[i64]]) -> CnfFormula {
+        let mut formula = CnfFormula::new(0);
+        for clause in clauses(list) {
+            formula.add_clause(clause);
+        }
+        formula
+    }
+
+    #[test]
+    fn test_formats_round_trip() {
+        let steps = vec![
+            ProofStep::Add(clauses(&[&[1, -200]]).remove(0)),
+            ProofStep::Delete(clauses(&[&[-3]]).remove(0)),
+            ProofStep::Add(vec![]),
+        ];
+        for format in [ProofFormat::Text, ProofFormat::Binary] {
+            let mut out = vec![];
+            for step in &steps {
+                format.write_step(&mut out, step).unwrap();
+            }
+            assert_eq!(format.parse(&out).unwrap(), steps);
+        }
+        let mut text = vec![];
+        ProofFormat::Text.write_step(&mut text, &steps[1]).unwrap();
+        assert_eq!(text, b"d -3 0\n");
+        let mut binary = vec![];
+        ProofFormat::Binary.write_step(&mut binary, &steps[0]).unwrap();
+        assert_eq!(binary, [b'a', 2, 0x91, 0x03, 0]);
+        assert_eq!(ProofFormat::Text.parse(b"1 x 0\n"), Err(DratError::Malformed { position: 1 }));
+        assert_eq!(ProofFormat::Binary.parse(b"a\x02"), Err(DratError::Malformed { position: 2 }));
+        assert_eq!(ProofFormat::Text.parse(b"-4294967296 0\n"), Err(DratError::Malformed { position: 1 }));
+    }
+
+    #[test]
+    fn test_check_rup_proof() {
+        let formula = formula(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+        assert_eq!(check_drat(&formula, b"2 0\n0\n", ProofFormat::Text), Ok(()));
+        assert_eq!(check_drat(&formula, b"2 0\n", ProofFormat::Text), Ok(()));
+        assert_eq!(check_drat(&formula, b"", ProofFormat::Text), Err(DratError::NoRefutation));
+        assert_eq!(
+            check_drat(&formula, b"d 1 2 0\nd -1 2 0\n-2 0\n2 0\n0\n", ProofFormat::Text),
+            Err(DratError::InvalidLemma { step: 3, clause: vec![2] })
+        );
+    }
+
+    #[test]
+    fn test_check_rat_lemma() {
+        // `1 -2` is blocked on `1`: its only resolvent with `-1 2` is a tautology.
+        let mut checker = Checker::default();
+        checker.push(&clauses(&[&[-1, 2]])[0]);
+        let lemma = checker.push(&clauses(&[&[1, -2]])[0]);
+        checker.set_active(lemma, false);
+        assert!(!checker.rup(&checker.clauses[lemma].clone()));
+        assert!(checker.verify(lemma));
+
+        let satisfiable = formula(&[&[1, 2]]);
+        assert_eq!(
+            check_drat(&satisfiable, b"-1 0\n0\n", ProofFormat::Text),
+            Err(DratError::InvalidLemma { step: 1, clause: vec![] })
+        );
+    }
+
+    #[test]
+    fn test_lemma_used_through_marked_clause() {
+        // Satisfiable, with 1, 2 and 3 true. The empty clause only uses `1 2` through the
+        // clause `1`, marked first by the refutation.
+        let satisfiable = formula(&[&[-1, 2], &[-2, 3], &[-1, -3], &[1, -3, -4], &[1, 4]]);
+        assert!(super::super::cdcl::solve(&satisfiable).is_sat());
+        assert_eq!(
+            check_drat(&satisfiable, b"1 2 0\n1 0\n0\n", ProofFormat::Text),
+            Err(DratError::InvalidLemma { step: 0, clause: vec![1, 2] })
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use crate::boolean_evaluator::boolean_evaluator::Evaluator;
+
+use super::cdcl::{Solver, Stats};
+use super::cnf::{CnfFormula, Lit, SolveResult};
+use super::tseitin::Encoding;
+
+/// Solver kept alive across many related queries. Formulas are encoded once over named
+/// variables, clauses can be added between calls, and the clauses learnt by one call speed
+/// up the next ones.
+#[derive(Debug)]
+pub struct IncrementalSolver {
+    solver: Solver<'static>,
+    /// Solver variable of each named variable.
+    variables: BTreeMap<String, usize>,
+}
+
+impl Default for IncrementalSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncrementalSolver {
+    pub fn new() -> Self {
+        IncrementalSolver { solver: Solver::new(&CnfFormula::new(0)), variables: BTreeMap::new() }
+    }
+
+    /// Positive literal of the variable called `name`, allocated on first use.
+    pub fn variable(&mut self, name: &str) -> Lit {
+        let variable = match self.variables.get(name) {
+            Some(&variable) => variable,
+            None => {
+                let variable = self.solver.new_variable();
+                self.variables.insert(name.to_string(), variable);
+                variable
+            }
+        };
+        Lit::positive(variable)
+    }
+
+    /// Encodes `evaluator` without asserting it. The returned literal implies the formula,
+    /// so assuming it asks for the formula to hold; its negation says nothing.
+    pub fn gate(&mut self, evaluator: &Evaluator) -> Lit {
+        let (encoding, roots) = Encoding::gates(&[evaluator]);
+        // Original variables map to the named ones, auxiliary variables to fresh ones.
+        let mut mapping = encoding
+            .variables()
+            .names()
+            .iter()
+            .map(|name| self.variable(name).variable())
+            .collect::<Vec<usize>>();
+        for _ in mapping.len()..encoding.formula().variable_count() {
+            mapping.push(self.solver.new_variable());
+        }
+        let translate = |lit: Lit| Lit::new(mapping[lit.variable()], lit.is_negated());
+        for clause in encoding.formula().clauses() {
+            self.solver.add_clause(&clause.iter().map(|&lit| translate(lit)).collect::<Vec<Lit>>());
+        }
+        translate(roots[0])
+    }
+
+    /// Asserts `evaluator` for every later call.
+    pub fn add_formula(&mut self, evaluator: &Evaluator) {
+        let gate = self.gate(evaluator);
+        self.solver.add_clause(&[gate]);
+    }
+
+    /// Asserts a clause over literals from `variable` or `gate` for every later call.
+    pub fn add_clause(&mut self, clause: &[Lit]) {
+        self.solver.add_clause(clause);
+    }
+
+    pub fn solve(&mut self) -> SolveResult {
+        self.solver.solve()
+    }
+
+    /// Decides the formulas added so far with `assumptions` forced true for this call only.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> SolveResult {
+        self.solver.solve_with_assumptions(assumptions)
+    }
+
+    /// Subset of the assumptions of the last call that is already unsatisfiable, empty when
+    /// the added formulas alone are.
+    pub fn failed_assumptions(&self) -> &[Lit] {
+        self.solver.failed_assumptions()
+    }
+
+    /// Values of the named variables in a model returned by `solve`.
+    pub fn assignment(&self, model: &[bool]) -> BTreeMap<String, bool> {
+        self.variables.iter().map(|(name, &variable)| (name.clone(), model[variable])).collect()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.solver.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queries_share_the_base_formula() {
+        let mut solver = IncrementalSolver::new();
+        solver.add_formula(&Evaluator::from_infix("(a -> b) & (b -> c) & (a | d)").unwrap());
+        let (a, c, d) = (solver.variable("a"), solver.variable("c"), solver.variable("d"));
+
+        let SolveResult::Sat(model) = solver.solve_with_assumptions(&[!d]) else { panic!("a can hold") };
+        let assignment = solver.assignment(&model);
+        assert_eq!(assignment.keys().collect::<Vec<_>>(), ["a", "b", "c", "d"]);
+        assert!(assignment["a"] && assignment["b"] && assignment["c"]);
+
+        assert_eq!(solver.solve_with_assumptions(&[!c, !d]), SolveResult::Unsat);
+        let mut failed = solver.failed_assumptions().to_vec();
+        failed.sort();
+        assert_eq!(failed, [!c, !d]);
+        assert!(solver.solve_with_assumptions(&[a]).is_sat());
+
+        solver.add_clause(&[!c]);
+        assert_eq!(solver.solve_with_assumptions(&[a]), SolveResult::Unsat);
+        assert_eq!(solver.failed_assumptions(), [a]);
+        assert!(solver.solve().is_sat());
+    }
+
+    #[test]
+    fn test_assumed_gates() {
+        let mut solver = IncrementalSolver::new();
+        solver.add_formula(&Evaluator::from_infix("x ^ y").unwrap());
+        let both = solver.gate(&Evaluator::from_infix("x & y").unwrap());
+        let neither = solver.gate(&Evaluator::from_infix("!x & !y").unwrap());
+        let either = solver.gate(&Evaluator::from_infix("x | y | z").unwrap());
+        assert_eq!(solver.solve_with_assumptions(&[both]), SolveResult::Unsat);
+        assert_eq!(solver.solve_with_assumptions(&[either, neither]), SolveResult::Unsat);
+        assert_eq!(solver.failed_assumptions(), [neither]);
+        let SolveResult::Sat(model) = solver.solve_with_assumptions(&[either]) else { panic!("x ^ y holds") };
+        assert_eq!(solver.assignment(&model).len(), 3);
+    }
+
+    #[test]
+    fn test_many_queries() {
+        let names = (0..40).map(|i| format!("x{i:02}")).collect::<Vec<String>>();
+        let mut solver = IncrementalSolver::new();
+        solver.add_formula(&Evaluator::new(&(names.join(" ") + &" ^".repeat(39))));
+        let lits = names.iter().map(|name| solver.variable(name)).collect::<Vec<Lit>>();
+        for query in 0..200 {
+            let assumptions = (0..39).map(|i| if (query >> (i % 8)) & 1 == 1 { lits[i] } else { !lits[i] }).collect::<Vec<Lit>>();
+            let SolveResult::Sat(model) = solver.solve_with_assumptions(&assumptions) else { panic!("x39 is free") };
+            let ones = assumptions.iter().filter(|lit| !lit.is_negated()).count();
+            assert_eq!(model[lits[39].variable()], ones % 2 == 0);
+        }
+    }
+}
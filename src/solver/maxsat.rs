@@ -0,0 +1,361 @@
+use std::io;
+
+use crate::error::DimacsError;
+
+use super::cdcl::Solver;
+use super::cnf::{CnfFormula, Lit, SolveResult};
+
+/// Weighted partial MaxSAT problem: hard clauses that must hold and weighted soft clauses
+/// whose falsified total weight is to be minimised.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WeightedCnf {
+    variable_count: usize,
+    hard: Vec<Vec<Lit>>,
+    soft: Vec<(Vec<Lit>, u64)>,
+}
+
+impl WeightedCnf {
+    pub fn new(variable_count: usize) -> Self {
+        WeightedCnf { variable_count, ..Default::default() }
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.variable_count
+    }
+
+    pub fn add_hard(&mut self, clause: Vec<Lit>) {
+        self.reserve(&clause);
+        self.hard.push(clause);
+    }
+
+    pub fn add_soft(&mut self, clause: Vec<Lit>, weight: u64) {
+        self.reserve(&clause);
+        self.soft.push((clause, weight));
+    }
+
+    fn reserve(&mut self, clause: &[Lit]) {
+        if let Some(max) = clause.iter().map(|lit| lit.variable() + 1).max() {
+            self.variable_count = self.variable_count.max(max);
+        }
+    }
+
+    pub fn hard(&self) -> &[Vec<Lit>] {
+        &self.hard
+    }
+
+    pub fn soft(&self) -> &[(Vec<Lit>, u64)] {
+        &self.soft
+    }
+
+    /// Total weight of the soft clauses `model` falsifies, or `None` when it falsifies a
+    /// hard clause.
+    pub fn cost(&self, model: &[bool]) -> Option<u64> {
+        let satisfied = |clause: &[Lit]| clause.iter().any(|lit| lit.apply(model[lit.variable()]));
+        if !self.hard.iter().all(|clause| satisfied(clause)) {
+            return None;
+        }
+        Some(self.soft.iter().filter(|(clause, _)| !satisfied(clause)).map(|(_, weight)| weight).sum())
+    }
+}
+
+/// Optimal solution of a `WeightedCnf`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Optimum {
+    pub cost: u64,
+    /// Assignment indexed by variable reaching `cost`.
+    pub model: Vec<bool>,
+}
+
+/// Soft clause as the solver sees it: `lits` with the relaxation variables of the cores it
+/// took part in, switched on by assuming `!selector`.
+struct Soft {
+    lits: Vec<Lit>,
+    weight: u64,
+    selector: Lit,
+}
+
+impl Soft {
+    fn new(solver: &mut Solver, lits: Vec<Lit>, weight: u64) -> Self {
+        let selector = Lit::positive(solver.new_variable());
+        solver.add_clause(&lits.iter().copied().chain([selector]).collect::<Vec<Lit>>());
+        Soft { lits, weight, selector }
+    }
+}
+
+/// Minimum-cost assignment satisfying the hard clauses of `formula`, or `None` when they are
+/// unsatisfiable.
+///
+/// Uses WPM1, the weighted Fu–Malik algorithm: while the enabled soft clauses are
+/// unsatisfiable, the solver's failed assumptions give a core, whose lightest weight is added
+/// to the cost. Every clause of the core gets a fresh relaxation variable, exactly one of which
+/// may be set, and clauses heavier than the core weight keep a copy with the remaining weight.
+/// The first satisfiable call is optimal.
+pub fn solve_maxsat(formula: &WeightedCnf) -> Option<Optimum> {
+    let mut solver = Solver::new(&CnfFormula::new(formula.variable_count()));
+    for clause in formula.hard() {
+        solver.add_clause(clause);
+    }
+    let mut softs = formula
+        .soft()
+        .iter()
+        .filter(|(_, weight)| *weight > 0)
+        .map(|(clause, weight)| Soft::new(&mut solver, clause.clone(), *weight))
+        .collect::<Vec<Soft>>();
+
+    loop {
+        let assumptions = softs.iter().map(|soft| !soft.selector).collect::<Vec<Lit>>();
+        if let SolveResult::Sat(mut model) = solver.solve_with_assumptions(&assumptions) {
+            model.truncate(formula.variable_count());
+            let cost = formula.cost(&model).expect("the solver satisfies the hard clauses");
+            return Some(Optimum { cost, model });
+        }
+        let failed = solver.failed_assumptions().to_vec();
+        if failed.is_empty() {
+            return None;
+        }
+        let (core, mut rest): (Vec<Soft>, Vec<Soft>) =
+            softs.into_iter().partition(|soft| failed.contains(&!soft.selector));
+        let weight = core.iter().map(|soft| soft.weight).min().expect("a core has clauses");
+
+        let mut relaxations = vec![];
+        for soft in core {
+            let relaxation = Lit::positive(solver.new_variable());
+            relaxations.push(relaxation);
+            let lits = soft.lits.iter().copied().chain([relaxation]).collect();
+            rest.push(Soft::new(&mut solver, lits, weight));
+            if soft.weight > weight {
+                rest.push(Soft { weight: soft.weight - weight, ..soft });
+            } else {
+                solver.add_clause(&[soft.selector]);
+            }
+        }
+        exactly_one(&mut solver, &relaxations);
+        softs = rest;
+    }
+}
+
+/// Adds clauses allowing exactly one of `lits`, with a sequential counter for the at most
+/// one part.
+fn exactly_one(solver: &mut Solver, lits: &[Lit]) {
+    solver.add_clause(lits);
+    // `prefix` holds once one of the literals before the current one does.
+    let mut prefix: Option<Lit> = None;
+    for &lit in lits {
+        let next = Lit::positive(solver.new_variable());
+        solver.add_clause(&[!lit, next]);
+        if let Some(prefix) = prefix {
+            solver.add_clause(&[!prefix, !lit]);
+            solver.add_clause(&[!prefix, next]);
+        }
+        prefix = Some(next);
+    }
+}
+
+/// Reads a weighted partial MaxSAT problem in WCNF. Both formats are accepted: the classic
+/// one, with a `p wcnf <variables> <clauses> [<top>]` header and clauses weighing `top` or
+/// more being hard, and the header-less one in which hard clauses start with `h`.
+pub fn parse_wcnf(input: &str) -> Result<WeightedCnf, DimacsError> {
+    let mut header: Option<(usize, usize, Option<u64>)> = None;
+    let mut formula = WeightedCnf::new(0);
+    let mut clauses = 0;
+    // Weight of the clause being read, `Some(None)` for a hard clause.
+    let mut weight: Option<Option<u64>> = None;
+    let mut clause = vec![];
+    let mut last_line = 0;
+
+    for (number, line) in input.lines().enumerate() {
+        let line_number = number + 1;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            None | Some("c") => continue,
+            Some("p") => {
+                let fields = tokens.collect::<Vec<&str>>();
+                let parsed = match fields[..] {
+                    ["wcnf", variables, count] => Some((variables, count, None)),
+                    ["wcnf", variables, count, top] => Some((variables, count, Some(top))),
+                    _ => None,
+                }
+                .and_then(|(variables, count, top)| match (variables.parse(), count.parse(), top.map(str::parse)) {
+                    (Ok(variables), Ok(count), None) => Some((variables, count, None)),
+                    (Ok(variables), Ok(count), Some(Ok(top))) => Some((variables, count, Some(top))),
+                    _ => None,
+                })
+                .filter(|&(variables, _, _)| variables <= Lit::MAX_VARIABLE + 1);
+                match (header, parsed, clauses) {
+                    (None, Some(parsed), 0) => {
+                        header = Some(parsed);
+                        formula.variable_count = parsed.0;
+                    }
+                    _ => return Err(DimacsError::InvalidHeader { line: line_number }),
+                }
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        for token in line.split_whitespace() {
+            let Some(current) = weight else {
+                weight = Some(if token == "h" {
+                    None
+                } else {
+                    let value = token
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|&value| value > 0)
+                        .ok_or_else(|| DimacsError::InvalidWeight { line: line_number, token: token.to_string() })?;
+                    match header {
+                        Some((_, _, Some(top))) if value >= top => None,
+                        _ => Some(value),
+                    }
+                });
+                continue;
+            };
+            let value: i64 = token
+                .parse()
+                .map_err(|_| DimacsError::InvalidLiteral { line: line_number, token: token.to_string() })?;
+            let limit = header.map_or(Lit::MAX_VARIABLE + 1, |(variable_count, _, _)| variable_count);
+            if value.unsigned_abs() > limit as u64 {
+                return Err(DimacsError::VariableOutOfRange { line: line_number, variable: value.unsigned_abs(), limit });
+            }
+            if value != 0 {
+                clause.push(Lit::from_dimacs(value));
+                continue;
+            }
+            match current {
+                None => formula.add_hard(std::mem::take(&mut clause)),
+                Some(value) => formula.add_soft(std::mem::take(&mut clause), value),
+            }
+            weight = None;
+            clauses += 1;
+        }
+        last_line = line_number;
+    }
+
+    if weight.is_some() {
+        return Err(DimacsError::UnterminatedClause { line: last_line });
+    }
+    if let Some((_, expected, _)) = header {
+        if clauses != expected {
+            return Err(DimacsError::ClauseCountMismatch { expected, found: clauses });
+        }
+    }
+    Ok(formula)
+}
+
+/// Writes `formula` in the classic WCNF format, giving hard clauses a `top` weight one above
+/// the total soft weight.
+pub fn write_wcnf(formula: &WeightedCnf, out: &mut dyn io::Write) -> io::Result<()> {
+    let top = formula.soft().iter().fold(1u64, |total, (_, weight)| total.saturating_add(*weight));
+    let count = formula.hard().len() + formula.soft().len();
+    writeln!(out, "p wcnf {} {count} {top}", formula.variable_count())?;
+    let hard = formula.hard().iter().map(|clause| (clause, top));
+    let soft = formula.soft().iter().map(|(clause, weight)| (clause, *weight));
+    for (clause, weight) in hard.chain(soft) {
+        write!(out, "{weight} ")?;
+        for lit in clause {
+            write!(out, "{} ", lit.to_dimacs())?;
+        }
+        writeln!(out, "0")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lits(values: &[i64]) -> Vec<Lit> {
+        values.iter().map(|&value| Lit::from_dimacs(value)).collect()
+    }
+
+    /// Lowest cost over every assignment, for small formulas.
+    fn brute_force(formula: &WeightedCnf) -> Option<u64> {
+        let count = formula.variable_count();
+        (0..1u64 << count)
+            .filter_map(|row| formula.cost(&(0..count).map(|variable| row >> variable & 1 == 1).collect::<Vec<bool>>()))
+            .min()
+    }
+
+    #[test]
+    fn test_weighted_partial_maxsat() {
+        let mut formula = WeightedCnf::new(3);
+        formula.add_hard(lits(&[1, 2]));
+        formula.add_hard(lits(&[-1, -3]));
+        formula.add_soft(lits(&[-1]), 4);
+        formula.add_soft(lits(&[-2]), 3);
+        formula.add_soft(lits(&[3]), 2);
+        formula.add_soft(lits(&[2, -3]), 1);
+        let optimum = solve_maxsat(&formula).unwrap();
+        assert_eq!(optimum.cost, 3);
+        assert_eq!(formula.cost(&optimum.model), Some(3));
+        assert_eq!(optimum.model, [false, true, true]);
+
+        formula.add_hard(lits(&[-2]));
+        formula.add_hard(lits(&[-1]));
+        assert_eq!(solve_maxsat(&formula), None);
+        assert_eq!(solve_maxsat(&WeightedCnf::new(2)), Some(Optimum { cost: 0, model: vec![false, false] }));
+    }
+
+    #[test]
+    fn test_agrees_with_brute_force() {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+        for round in 0..40 {
+            let mut formula = WeightedCnf::new(8);
+            let mut random_clause = |length| {
+                (0..length).map(|_| Lit::new(next(8) as usize, next(2) == 1)).collect::<Vec<Lit>>()
+            };
+            for _ in 0..6 {
+                formula.add_hard(random_clause(3));
+            }
+            for _ in 0..14 {
+                let clause = random_clause(2);
+                formula.add_soft(clause, 1 + round % 3 * (round % 7));
+            }
+            let optimum = solve_maxsat(&formula);
+            assert_eq!(optimum.as_ref().map(|optimum| optimum.cost), brute_force(&formula), "round {round}");
+            if let Some(optimum) = optimum {
+                assert_eq!(formula.cost(&optimum.model), Some(optimum.cost));
+            }
+        }
+    }
+
+    #[test]
+    fn test_wcnf_round_trip() {
+        let input = "c example\np wcnf 3 4 10\n10 1 2 0\n3 -1 0\n4 -2\n3 0\n10 -3 0\n";
+        let formula = parse_wcnf(input).unwrap();
+        assert_eq!(formula.hard(), [lits(&[1, 2]), lits(&[-3])]);
+        assert_eq!(formula.soft(), [(lits(&[-1]), 3), (lits(&[-2, 3]), 4)]);
+
+        let mut out = vec![];
+        write_wcnf(&formula, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text, "p wcnf 3 4 8\n8 1 2 0\n8 -3 0\n3 -1 0\n4 -2 3 0\n");
+        assert_eq!(parse_wcnf(&text).unwrap(), formula);
+
+        let modern = parse_wcnf("h 1 2 0\nh -3 0\n3 -1 0\n4 -2 3 0\n").unwrap();
+        assert_eq!(modern, formula);
+        assert_eq!(solve_maxsat(&modern).unwrap().cost, 3);
+    }
+
+    #[test]
+    fn test_wcnf_errors() {
+        assert_eq!(
+            parse_wcnf("p wcnf 2 1\nx 1 0\n"),
+            Err(DimacsError::InvalidWeight { line: 2, token: "x".to_string() })
+        );
+        assert_eq!(parse_wcnf("p wcnf 2 1\n0 1 0\n").unwrap_err(), DimacsError::InvalidWeight { line: 2, token: "0".to_string() });
+        assert_eq!(parse_wcnf("p cnf 2 1\n1 1 0\n"), Err(DimacsError::InvalidHeader { line: 1 }));
+        assert_eq!(
+            parse_wcnf("p wcnf 2 1\n1 3 0\n"),
+            Err(DimacsError::VariableOutOfRange { line: 2, variable: 3, limit: 2 })
+        );
+        assert_eq!(parse_wcnf("h 1 2\n"), Err(DimacsError::UnterminatedClause { line: 1 }));
+        assert_eq!(parse_wcnf("p wcnf 2 2\n1 1 0\n"), Err(DimacsError::ClauseCountMismatch { expected: 2, found: 1 }));
+    }
+}
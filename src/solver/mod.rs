@@ -0,0 +1,12 @@
+pub mod cdcl;
+pub mod cnf;
+pub mod core;
+pub mod counting;
+pub mod dimacs;
+pub mod dpll;
+pub mod drat;
+pub mod incremental;
+pub mod maxsat;
+pub mod models;
+pub mod tseitin;
+pub mod validity;
\ No newline at end of file
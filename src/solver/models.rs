@@ -0,0 +1,193 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::boolean_evaluator::{
+    boolean_evaluator::Evaluator,
+    compiled::CompiledEvaluator,
+};
+use crate::error::FormulaError;
+
+use super::cdcl::Solver;
+use super::cnf::{Lit, SolveResult};
+use super::tseitin::Encoding;
+
+/// Formulas with at most this many variables are enumerated and counted from their truth
+/// table, larger ones with the solver.
+pub const TABLE_VARIABLE_LIMIT: usize = 16;
+
+/// Lazy iterator over the satisfying assignments of a formula, each listed once.
+///
+/// Small formulas yield their models in truth table row order; large ones in the order the
+/// solver finds them, each found model being excluded by a blocking clause before the next.
+pub struct Models {
+    /// Names of the variables each model assigns, sorted.
+    names: Vec<String>,
+    source: Source,
+}
+
+enum Source {
+    Table {
+        compiled: CompiledEvaluator,
+        /// Table index of each projected variable.
+        projection: Vec<usize>,
+        next_row: u64,
+        block: Option<(u64, u64)>,
+        seen: HashSet<Vec<bool>>,
+    },
+    Solver {
+        solver: Box<Solver<'static>>,
+        projection: Vec<usize>,
+        done: bool,
+    },
+}
+
+/// Every satisfying assignment of `evaluator` over all of its variables.
+pub fn models(evaluator: &Evaluator) -> Models {
+    let variables = evaluator.variables();
+    projected_models(evaluator, &variables).expect("a formula's own variables occur in it")
+}
+
+/// Distinct restrictions of the satisfying assignments of `evaluator` to `projection`,
+/// that is the assignments of `projection` that extend to a model.
+pub fn projected_models<S: AsRef<str>>(evaluator: &Evaluator, projection: &[S]) -> Result<Models, FormulaError> {
+    let variables = evaluator.variable_table();
+    let mut names = projection.iter().map(|name| name.as_ref().to_string()).collect::<Vec<String>>();
+    names.sort();
+    names.dedup();
+    let projection = names
+        .iter()
+        .map(|name| variables.index(name).ok_or_else(|| FormulaError::UnknownVariable { variable: name.clone() }))
+        .collect::<Result<Vec<usize>, FormulaError>>()?;
+
+    let source = if variables.len() <= TABLE_VARIABLE_LIMIT {
+        Source::Table {
+            compiled: CompiledEvaluator::new(evaluator, &variables)?,
+            projection,
+            next_row: 0,
+            block: None,
+            seen: HashSet::new(),
+        }
+    } else {
+        // The encoding keeps the indices of the original variables.
+        let encoding = Encoding::plaisted_greenbaum(evaluator);
+        Source::Solver { solver: Box::new(Solver::new(encoding.formula())), projection, done: false }
+    };
+    Ok(Models { names, source })
+}
+
+impl Models {
+    fn assignment(&self, values: impl Iterator<Item = bool>) -> BTreeMap<String, bool> {
+        self.names.iter().cloned().zip(values).collect()
+    }
+}
+
+impl Iterator for Models {
+    type Item = BTreeMap<String, bool>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            Source::Table { compiled, projection, next_row, block, seen } => {
+                let count = compiled.variable_count();
+                while *next_row < compiled.row_count() {
+                    let row = *next_row;
+                    *next_row += 1;
+                    let word = match *block {
+                        Some((index, word)) if index == row / 64 => word,
+                        _ => {
+                            let word = compiled.eval_block(row / 64);
+                            *block = Some((row / 64, word));
+                            word
+                        }
+                    };
+                    if word >> (row % 64) & 1 == 0 {
+                        continue;
+                    }
+                    let values = projection
+                        .iter()
+                        .map(|&variable| row >> (count - 1 - variable) & 1 == 1)
+                        .collect::<Vec<bool>>();
+                    if projection.len() == count || seen.insert(values.clone()) {
+                        return Some(self.assignment(values.into_iter()));
+                    }
+                }
+                None
+            }
+            Source::Solver { solver, projection, done } => {
+                if *done {
+                    return None;
+                }
+                match solver.solve() {
+                    SolveResult::Unsat => {
+                        *done = true;
+                        None
+                    }
+                    SolveResult::Sat(model) => {
+                        let blocking = projection
+                            .iter()
+                            .map(|&variable| Lit::new(variable, model[variable]))
+                            .collect::<Vec<Lit>>();
+                        solver.add_clause(&blocking);
+                        let values = projection.iter().map(|&variable| model[variable]).collect::<Vec<bool>>();
+                        Some(self.assignment(values.into_iter()))
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide_or(count: usize) -> Evaluator {
+        let names = (0..count).map(|i| format!("x{i:02}")).collect::<Vec<String>>();
+        Evaluator::new(&(names.join(" ") + &" |".repeat(count - 1)))
+    }
+
+    #[test]
+    fn test_models_from_truth_table() {
+        let evaluator = Evaluator::new("AB|C&");
+        let models = models(&evaluator).collect::<Vec<BTreeMap<String, bool>>>();
+        assert_eq!(models.len(), 3);
+        assert_eq!(models[0], BTreeMap::from([("A".to_string(), false), ("B".to_string(), true), ("C".to_string(), true)]));
+        assert!(models.iter().all(|model| evaluator.eval(model).unwrap()));
+        assert_eq!(super::models(&Evaluator::new("AA!&")).count(), 0);
+        assert_eq!(super::models(&Evaluator::new("1")).collect::<Vec<_>>(), vec![BTreeMap::new()]);
+    }
+
+    #[test]
+    fn test_projected_models() {
+        let evaluator = Evaluator::new("AB|C&");
+        let models = projected_models(&evaluator, &["C", "A"]).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            models,
+            vec![
+                BTreeMap::from([("A".to_string(), false), ("C".to_string(), true)]),
+                BTreeMap::from([("A".to_string(), true), ("C".to_string(), true)]),
+            ]
+        );
+        assert_eq!(
+            projected_models(&evaluator, &["D"]).err(),
+            Some(FormulaError::UnknownVariable { variable: "D".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_models_from_solver() {
+        let evaluator = Evaluator::new(&format!("{} x00 x01 ^ &", wide_or(18)));
+        let models = models(&evaluator).take(1000).collect::<Vec<_>>();
+        assert_eq!(models.len(), 1000);
+        assert!(models.iter().all(|model| model.len() == 18 && evaluator.eval(model).unwrap()));
+        assert_eq!(models.iter().collect::<HashSet<_>>().len(), 1000);
+
+        let projected = projected_models(&evaluator, &["x00", "x01", "x02"]).unwrap().collect::<HashSet<_>>();
+        assert_eq!(projected.len(), 4);
+    }
+
+    #[test]
+    fn test_solver_and_table_agree() {
+        let evaluator = wide_or(17);
+        assert_eq!(projected_models(&evaluator, &["x03", "x09"]).unwrap().count(), 4);
+        assert_eq!(projected_models(&Evaluator::new(&format!("{} x03 ! x03 & &", wide_or(17))), &["x03"]).unwrap().count(), 0);
+    }
+}
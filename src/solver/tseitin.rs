@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+
+use crate::boolean_evaluator::{boolean_evaluator::Evaluator, variables::VariableTable};
+
+use super::cnf::{CnfFormula, Lit};
+
+/// Equisatisfiable clause set of a formula, linear in its size. Variables `0..variables().len()`
+/// are the formula's own, in sorted name order; the ones above are auxiliary, one per gate.
+#[derive(Debug, Clone)]
+pub struct Encoding {
+    formula: CnfFormula,
+    variables: VariableTable,
+}
+
+impl Encoding {
+    /// Tseitin encoding: each auxiliary variable is equivalent to its gate, so every model of
+    /// the formula extends to exactly one model of the clauses.
+    pub fn tseitin(evaluator: &Evaluator) -> Self {
+        Self::encode(evaluator, true)
+    }
+
+    /// Plaisted–Greenbaum encoding: each auxiliary variable only implies its gate in the
+    /// polarity it is used with, which drops about half of the clauses.
+    pub fn plaisted_greenbaum(evaluator: &Evaluator) -> Self {
+        Self::encode(evaluator, false)
+    }
+
+    fn encode(evaluator: &Evaluator, full: bool) -> Self {
+        let (mut encoding, roots) = Self::encode_all(&[evaluator], full);
+        encoding.formula.add_clause(roots);
+        encoding
+    }
+
+    /// Plaisted–Greenbaum encoding of several formulas over their shared variables, without
+    /// asserting them. Each returned literal implies its formula, so a solver can switch the
+    /// formulas on through assumptions.
+    pub fn gates(evaluators: &[&Evaluator]) -> (Self, Vec<Lit>) {
+        Self::encode_all(evaluators, false)
+    }
+
+    fn encode_all(evaluators: &[&Evaluator], full: bool) -> (Self, Vec<Lit>) {
+        let mut names = evaluators.iter().flat_map(|evaluator| evaluator.variables()).collect::<Vec<String>>();
+        names.sort();
+        names.dedup();
+        let variables = names.into_iter().collect::<VariableTable>();
+        let mut encoder = Encoder { formula: CnfFormula::new(variables.len()), variables: &variables, full, truth: None };
+        let roots = evaluators
+            .iter()
+            .map(|evaluator| encoder.encode(evaluator, Polarity::POSITIVE))
+            .collect();
+        let formula = encoder.formula;
+        (Encoding { formula, variables }, roots)
+    }
+
+    pub fn formula(&self) -> &CnfFormula {
+        &self.formula
+    }
+
+    /// Names of the original variables, which keep their indices in `formula()`.
+    pub fn variables(&self) -> &VariableTable {
+        &self.variables
+    }
+
+    pub fn is_auxiliary(&self, variable: usize) -> bool {
+        variable >= self.variables.len()
+    }
+
+    /// Restricts a model of `formula()` to the original variables.
+    pub fn project(&self, model: &[bool]) -> BTreeMap<String, bool> {
+        self.variables.names().iter().cloned().zip(model.iter().copied()).collect()
+    }
+}
+
+/// Directions in which a gate's auxiliary variable must be tied to the gate.
+#[derive(Debug, Clone, Copy)]
+struct Polarity {
+    positive: bool,
+    negative: bool,
+}
+
+impl Polarity {
+    const POSITIVE: Polarity = Polarity { positive: true, negative: false };
+    const BOTH: Polarity = Polarity { positive: true, negative: true };
+
+    fn flip(self) -> Self {
+        Polarity { positive: self.negative, negative: self.positive }
+    }
+}
+
+struct Encoder<'a> {
+    formula: CnfFormula,
+    variables: &'a VariableTable,
+    full: bool,
+    /// Auxiliary variable forced true, allocated on the first constant.
+    truth: Option<Lit>,
+}
+
+impl Encoder<'_> {
+    fn fresh(&mut self) -> Lit {
+        Lit::positive(self.formula.new_variable())
+    }
+
+    /// Literal equivalent to `evaluator` in the directions of `polarity`.
+    fn encode(&mut self, evaluator: &Evaluator, polarity: Polarity) -> Lit {
+        let polarity = if self.full { Polarity::BOTH } else { polarity };
+        match evaluator {
+            Evaluator::True | Evaluator::False => {
+                let truth = match self.truth {
+                    Some(truth) => truth,
+                    None => {
+                        let truth = self.fresh();
+                        self.formula.add_clause(vec![truth]);
+                        *self.truth.insert(truth)
+                    }
+                };
+                if let Evaluator::True = evaluator { truth } else { !truth }
+            }
+            Evaluator::Variable(name) => Lit::positive(self.variables.index(name).unwrap()),
+            Evaluator::Not(operand) => !self.encode(operand, polarity.flip()),
+            Evaluator::And(..) => {
+                let lits = self.operands(evaluator, polarity);
+                self.and_gate(lits, polarity)
+            }
+            Evaluator::Or(..) => {
+                let lits = self.operands(evaluator, polarity);
+                self.or_gate(lits, polarity)
+            }
+            Evaluator::Nand(left, right) => {
+                let lits = vec![self.encode(left, polarity.flip()), self.encode(right, polarity.flip())];
+                !self.and_gate(lits, polarity.flip())
+            }
+            Evaluator::Nor(left, right) => {
+                let lits = vec![self.encode(left, polarity.flip()), self.encode(right, polarity.flip())];
+                !self.or_gate(lits, polarity.flip())
+            }
+            Evaluator::Conditional(left, right) | Evaluator::Converse(right, left) => {
+                let lits = vec![!self.encode(left, polarity.flip()), self.encode(right, polarity)];
+                self.or_gate(lits, polarity)
+            }
+            Evaluator::Xor(left, right) => {
+                let (left, right) = (self.encode(left, Polarity::BOTH), self.encode(right, Polarity::BOTH));
+                self.xor_gate(left, right, polarity)
+            }
+            Evaluator::Equivalence(left, right) | Evaluator::Xnor(left, right) => {
+                let (left, right) = (self.encode(left, Polarity::BOTH), self.encode(right, Polarity::BOTH));
+                !self.xor_gate(left, right, polarity.flip())
+            }
+            Evaluator::IfThenElse(condition, then, otherwise) => {
+                let condition = self.encode(condition, Polarity::BOTH);
+                let (then, otherwise) = (self.encode(then, polarity), self.encode(otherwise, polarity));
+                let gate = self.fresh();
+                if polarity.positive {
+                    self.formula.add_clause(vec![!gate, !condition, then]);
+                    self.formula.add_clause(vec![!gate, condition, otherwise]);
+                }
+                if polarity.negative {
+                    self.formula.add_clause(vec![gate, !condition, !then]);
+                    self.formula.add_clause(vec![gate, condition, !otherwise]);
+                }
+                gate
+            }
+        }
+    }
+
+    /// Encodes the operands of a chain of `And` or `Or` nodes, so the chain becomes a single
+    /// gate and its depth does not grow the stack.
+    fn operands(&mut self, evaluator: &Evaluator, polarity: Polarity) -> Vec<Lit> {
+        let same = |node: &Evaluator| std::mem::discriminant(node) == std::mem::discriminant(evaluator);
+        let mut pending = vec![evaluator];
+        let mut lits = vec![];
+        while let Some(node) = pending.pop() {
+            match node {
+                Evaluator::And(left, right) | Evaluator::Or(left, right) if same(node) => {
+                    pending.push(right);
+                    pending.push(left);
+                }
+                _ => lits.push(self.encode(node, polarity)),
+            }
+        }
+        lits
+    }
+
+    fn and_gate(&mut self, lits: Vec<Lit>, polarity: Polarity) -> Lit {
+        let gate = self.fresh();
+        if polarity.positive {
+            for &lit in &lits {
+                self.formula.add_clause(vec![!gate, lit]);
+            }
+        }
+        if polarity.negative {
+            self.formula.add_clause(std::iter::once(gate).chain(lits.iter().map(|&lit| !lit)).collect());
+        }
+        gate
+    }
+
+    fn or_gate(&mut self, lits: Vec<Lit>, polarity: Polarity) -> Lit {
+        !self.and_gate(lits.into_iter().map(|lit| !lit).collect(), polarity.flip())
+    }
+
+    fn xor_gate(&mut self, left: Lit, right: Lit, polarity: Polarity) -> Lit {
+        let gate = self.fresh();
+        if polarity.positive {
+            self.formula.add_clause(vec![!gate, left, right]);
+            self.formula.add_clause(vec![!gate, !left, !right]);
+        }
+        if polarity.negative {
+            self.formula.add_clause(vec![gate, !left, right]);
+            self.formula.add_clause(vec![gate, left, !right]);
+        }
+        gate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boolean_evaluator::assignment::Bitmask;
+    use crate::solver::{cdcl, cnf::SolveResult};
+
+    /// Whether the clauses with the original variables fixed to `row` are satisfiable.
+    fn extends(encoding: &Encoding, row: u64) -> bool {
+        let mut formula = encoding.formula().clone();
+        for variable in 0..encoding.variables().len() {
+            formula.add_clause(vec![Lit::new(variable, row & (1 << variable) == 0)]);
+        }
+        cdcl::solve(&formula).is_sat()
+    }
+
+    #[test]
+    fn test_encodings_are_equisatisfiable_per_assignment() {
+        for formula in ["AB^C=", "AB/C#!", "ABC?!", "AB%C<", "AB>!C|", "A0^B1=&", "ABC&&D|!"] {
+            let evaluator = Evaluator::new(formula);
+            let table = evaluator.variable_table();
+            for encoding in [Encoding::tseitin(&evaluator), Encoding::plaisted_greenbaum(&evaluator)] {
+                for row in 0..1u64 << table.len() {
+                    let expected = evaluator.eval(&Bitmask::new(&table, row)).unwrap();
+                    assert_eq!(extends(&encoding, row), expected, "{formula} row {row}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_xor_chain_is_linear() {
+        let names = (0..200).map(|i| format!("x{i}")).collect::<Vec<String>>();
+        let chain = Evaluator::new(&(names.join(" ") + &" ^".repeat(199)));
+        let encoding = Encoding::plaisted_greenbaum(&chain);
+        assert_eq!(encoding.formula().variable_count(), 200 + 199);
+        assert!(encoding.formula().clauses().len() <= 4 * 199 + 1);
+        assert!(encoding.is_auxiliary(200) && !encoding.is_auxiliary(199));
+
+        let SolveResult::Sat(model) = cdcl::solve(encoding.formula()) else { panic!("xor chain is satisfiable") };
+        let projection = encoding.project(&model);
+        assert_eq!(projection.len(), 200);
+        assert!(chain.eval(&projection).unwrap());
+    }
+
+    #[test]
+    fn test_plaisted_greenbaum_is_smaller() {
+        let evaluator = Evaluator::new("AB&CD&|EF&|");
+        let full = Encoding::tseitin(&evaluator).formula().clauses().len();
+        let polar = Encoding::plaisted_greenbaum(&evaluator).formula().clauses().len();
+        assert!(polar < full, "{polar} < {full}");
+    }
+}
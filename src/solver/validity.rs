@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use crate::boolean_evaluator::boolean_evaluator::Evaluator;
+
+use super::cdcl;
+use super::tseitin::Encoding;
+
+/// Outcome of a validity check: the claim holds on every assignment, or fails on the given one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Proven,
+    /// Assignment of every variable of the formulas involved on which the claim fails.
+    Counterexample(BTreeMap<String, bool>),
+}
+
+impl Verdict {
+    pub fn is_proven(&self) -> bool {
+        matches!(self, Verdict::Proven)
+    }
+
+    pub fn counterexample(&self) -> Option<&BTreeMap<String, bool>> {
+        match self {
+            Verdict::Proven => None,
+            Verdict::Counterexample(assignment) => Some(assignment),
+        }
+    }
+}
+
+/// Whether `evaluator` holds on every assignment.
+pub fn is_tautology(evaluator: &Evaluator) -> Verdict {
+    refute(Evaluator::Not(Box::new(evaluator.clone())))
+}
+
+/// Whether `evaluator` fails on every assignment; a counterexample satisfies it.
+pub fn is_contradiction(evaluator: &Evaluator) -> Verdict {
+    refute(evaluator.clone())
+}
+
+/// Whether every model of `premise` satisfies `conclusion`.
+pub fn entails(premise: &Evaluator, conclusion: &Evaluator) -> Verdict {
+    refute(Evaluator::And(Box::new(premise.clone()), Box::new(Evaluator::Not(Box::new(conclusion.clone())))))
+}
+
+/// Whether `left` and `right` agree on every assignment of the variables of either.
+pub fn equivalent(left: &Evaluator, right: &Evaluator) -> Verdict {
+    refute(Evaluator::Xor(Box::new(left.clone()), Box::new(right.clone())))
+}
+
+/// `Proven` when `evaluator` is unsatisfiable, otherwise one of its models.
+fn refute(evaluator: Evaluator) -> Verdict {
+    let encoding = Encoding::plaisted_greenbaum(&evaluator);
+    match cdcl::solve(encoding.formula()).model() {
+        None => Verdict::Proven,
+        Some(model) => Verdict::Counterexample(encoding.project(model)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formula(infix: &str) -> Evaluator {
+        Evaluator::from_infix(infix).unwrap()
+    }
+
+    #[test]
+    fn test_tautology_and_contradiction() {
+        assert_eq!(is_tautology(&formula("a | !a")), Verdict::Proven);
+        assert_eq!(is_tautology(&formula("(a -> b) | (b -> a)")), Verdict::Proven);
+        let verdict = is_tautology(&formula("a -> b"));
+        assert_eq!(verdict.counterexample(), Some(&BTreeMap::from([("a".to_string(), true), ("b".to_string(), false)])));
+
+        assert!(is_contradiction(&formula("a & !a")).is_proven());
+        let satisfiable = formula("a ^ b");
+        let model = is_contradiction(&satisfiable).counterexample().cloned().unwrap();
+        assert_eq!(satisfiable.eval(&model), Ok(true));
+    }
+
+    #[test]
+    fn test_entails() {
+        assert!(entails(&formula("a & (a -> b)"), &formula("b")).is_proven());
+        assert!(entails(&Evaluator::False, &formula("c")).is_proven());
+        let verdict = entails(&formula("a | b"), &formula("a & c"));
+        let model = verdict.counterexample().unwrap();
+        assert_eq!(model.keys().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(formula("(a | b) & !(a & c)").eval(model), Ok(true));
+    }
+
+    #[test]
+    fn test_equivalent() {
+        assert!(equivalent(&Evaluator::new("AB=!"), &Evaluator::new("AB^")).is_proven());
+        assert!(equivalent(&Evaluator::new("AB>"), &Evaluator::new("B!A!>")).is_proven());
+        // Variables of only one side are still assigned in the counterexample.
+        let verdict = equivalent(&Evaluator::new("AB|"), &Evaluator::new("AC|"));
+        let model = verdict.counterexample().unwrap();
+        assert_eq!(model.len(), 3);
+        assert!(!model["A"] && model["B"] != model["C"]);
+
+        let names = (0..200).map(|i| format!("x{i:03}")).collect::<Vec<String>>();
+        let chain = Evaluator::new(&(names.join(" ") + &" ^".repeat(199)));
+        let reversed = Evaluator::new(&(names.iter().rev().cloned().collect::<Vec<String>>().join(" ") + &" ^".repeat(199)));
+        assert!(equivalent(&chain, &reversed).is_proven());
+    }
+}